@@ -1,18 +1,28 @@
+use day1::{Frequencies, Frequency};
 use failure::Fallible;
-use std::io::{self, BufRead, BufReader, Read};
+use std::env;
+use std::fmt::Display;
+use std::io::{self, BufReader};
 use std::str::FromStr;
 
-fn sum_lines<R: Read>(reader: R) -> Fallible<isize> {
-    BufReader::new(reader)
-        .lines()
-        .map(|l| {
-            l.map_err(failure::Error::from)
-                .and_then(|l| isize::from_str(&l).map_err(failure::Error::from))
-        })
-        .sum()
+fn run<T>() -> Fallible<()>
+where
+    T: Frequency + FromStr + Send + Sync + 'static,
+    T::Err: Display,
+{
+    println!(
+        "{}",
+        Frequencies::<T>::read(BufReader::new(io::stdin()))?.sum()?
+    );
+    Ok(())
 }
 
 fn main() -> Fallible<()> {
-    println!("{}", sum_lines(io::stdin())?);
-    Ok(())
+    if env::args().any(|arg| arg == "--big") {
+        #[cfg(feature = "bigint")]
+        return run::<num_bigint::BigInt>();
+        #[cfg(not(feature = "bigint"))]
+        failure::bail!("--big needs the bigint feature");
+    }
+    run::<isize>()
 }
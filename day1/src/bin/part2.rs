@@ -1,25 +1,34 @@
+use day1::{Frequencies, Frequency};
 use failure::Fallible;
-use std::collections::HashSet;
-use std::io::{self, BufRead, BufReader};
+use std::env;
+use std::fmt::Display;
+use std::io::{self, BufReader};
 use std::str::FromStr;
 
-fn read_values<R: BufRead>(reader: R) -> impl Iterator<Item = Fallible<isize>> {
-    BufReader::new(reader).lines().map(|l| {
-        l.map_err(failure::Error::from)
-            .and_then(|l| isize::from_str(&l).map_err(failure::Error::from))
-    })
+fn run<T>() -> Fallible<()>
+where
+    T: Frequency + FromStr + Send + Sync + 'static,
+    T::Err: Display,
+{
+    let frequencies = Frequencies::<T>::read(BufReader::new(io::stdin()))?;
+    let repeat = frequencies.first_repeat()?;
+    println!("{}", repeat.frequency);
+    eprintln!(
+        "reached again by change {} in pass {} (step {}), first reached at step {}",
+        repeat.index,
+        repeat.cycle,
+        repeat.step(frequencies.changes().len()),
+        repeat.first_step
+    );
+    Ok(())
 }
 
 fn main() -> Fallible<()> {
-    let values = read_values(BufReader::new(io::stdin())).collect::<Result<Vec<_>, _>>()?;
-    let mut sum = 0;
-    let mut set = HashSet::new();
-    for value in values.iter().cycle() {
-        sum += value;
-        if !set.insert(sum) {
-            println!("{}", sum);
-            break;
-        }
+    if env::args().any(|arg| arg == "--big") {
+        #[cfg(feature = "bigint")]
+        return run::<num_bigint::BigInt>();
+        #[cfg(not(feature = "bigint"))]
+        failure::bail!("--big needs the bigint feature");
     }
-    Ok(())
+    run::<isize>()
 }
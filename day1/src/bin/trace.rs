@@ -0,0 +1,25 @@
+use day1::{Format, Frequencies};
+use failure::Fallible;
+use std::env;
+use std::io::{self, BufReader, BufWriter};
+
+fn main() -> Fallible<()> {
+    let mut format = Format::Csv;
+    let mut cycles = 1;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| failure::format_err!("{} needs a value", arg))
+        };
+        match arg.as_str() {
+            "--format" => format = value()?.parse()?,
+            "--cycles" => cycles = value()?.parse()?,
+            _ => failure::bail!("usage: trace [--format csv|json] [--cycles N]"),
+        }
+    }
+
+    let frequencies = Frequencies::<isize>::read(BufReader::new(io::stdin()))?;
+    let stdout = io::stdout();
+    frequencies.write_trace(BufWriter::new(stdout.lock()), format, cycles)
+}
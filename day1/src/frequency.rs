@@ -0,0 +1,69 @@
+use std::convert::TryFrom;
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+
+// The integer type frequencies are summed in. Apart from addition, the repeat finder only needs to
+// sort frequencies into residue classes modulo the drift and count how many drifts apart two of
+// them are, which fixed-width types can do without overflowing.
+pub trait Frequency: Clone + Ord + Hash + Debug + Display {
+    fn zero() -> Self;
+
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+
+    // Remainder of dividing by the magnitude of `drift`; never negative
+    fn residue(&self, drift: &Self) -> Self;
+
+    // How many times `drift` fits between `self` and `other`, if that fits in a usize
+    fn drifts_between(&self, other: &Self, drift: &Self) -> Option<usize>;
+}
+
+macro_rules! impl_frequency {
+    ($($t:ty)*) => {$(
+        impl Frequency for $t {
+            fn zero() -> $t {
+                0
+            }
+
+            fn checked_add(&self, other: &$t) -> Option<$t> {
+                <$t>::checked_add(*self, *other)
+            }
+
+            fn residue(&self, drift: &$t) -> $t {
+                // Only MIN % -1 overflows, and that divides evenly
+                self.checked_rem_euclid(*drift).unwrap_or(0)
+            }
+
+            fn drifts_between(&self, other: &$t, drift: &$t) -> Option<usize> {
+                usize::try_from(self.abs_diff(*other) / drift.unsigned_abs()).ok()
+            }
+        }
+    )*};
+}
+
+impl_frequency!(isize i64 i128);
+
+#[cfg(feature = "bigint")]
+impl Frequency for num_bigint::BigInt {
+    fn zero() -> num_bigint::BigInt {
+        num_traits::Zero::zero()
+    }
+
+    fn checked_add(&self, other: &num_bigint::BigInt) -> Option<num_bigint::BigInt> {
+        Some(self + other)
+    }
+
+    fn residue(&self, drift: &num_bigint::BigInt) -> num_bigint::BigInt {
+        use num_integer::Integer;
+        use num_traits::Signed;
+        self.mod_floor(&drift.abs())
+    }
+
+    fn drifts_between(
+        &self,
+        other: &num_bigint::BigInt,
+        drift: &num_bigint::BigInt,
+    ) -> Option<usize> {
+        use num_traits::{Signed, ToPrimitive};
+        ((self - other).abs() / drift.abs()).to_usize()
+    }
+}
@@ -0,0 +1,512 @@
+mod frequency;
+mod trace;
+
+pub use crate::frequency::Frequency;
+pub use crate::trace::{Format, Step, Trace};
+
+use failure::Fallible;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::io::BufRead;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frequencies<T = isize> {
+    changes: Vec<T>,
+    // The input line each change came from
+    lines: Vec<usize>,
+}
+
+impl<T: Frequency> Frequencies<T> {
+    // Changes can be separated by newlines or commas, with any surrounding whitespace. Blank lines
+    // and empty fields are skipped.
+    pub fn read<R: BufRead>(reader: R) -> Fallible<Frequencies<T>>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let mut changes = Vec::new();
+        let mut lines = Vec::new();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            let mut offset = 0;
+            for field in line.split(',') {
+                let start = offset + (field.len() - field.trim_start().len());
+                offset += field.len() + 1;
+                let field = field.trim();
+                if field.is_empty() {
+                    continue;
+                }
+                match T::from_str(field) {
+                    Ok(change) => changes.push(change),
+                    Err(err) => {
+                        return Err(ParseError {
+                            line: i + 1,
+                            column: line[..start].chars().count() + 1,
+                            text: line.clone(),
+                            reason: err.to_string(),
+                        }
+                        .into())
+                    }
+                }
+                lines.push(i + 1);
+            }
+        }
+        Ok(Frequencies { changes, lines })
+    }
+
+    pub fn changes(&self) -> &[T] {
+        &self.changes
+    }
+
+    pub fn sum(&self) -> Result<T, Overflow> {
+        let mut sum = T::zero();
+        for (i, change) in self.changes.iter().enumerate() {
+            sum = sum.checked_add(change).ok_or_else(|| self.overflow_at(i))?;
+        }
+        Ok(sum)
+    }
+
+    pub(crate) fn overflow_at(&self, index: usize) -> Overflow {
+        Overflow {
+            line: self.lines[index],
+        }
+    }
+
+    // The frequency before each change in the first pass, starting from 0, and the drift.
+    fn starting_points(&self) -> Result<(Vec<T>, T), Overflow> {
+        let mut sum = T::zero();
+        let mut sums = Vec::with_capacity(self.changes.len());
+        for (i, change) in self.changes.iter().enumerate() {
+            let next = sum.checked_add(change).ok_or_else(|| self.overflow_at(i))?;
+            sums.push(sum);
+            sum = next;
+        }
+        Ok((sums, sum))
+    }
+
+    // Every pass visits the same frequencies as the first one, shifted by the drift. With no drift
+    // the start frequency comes back at the end of the first pass. Otherwise two frequencies can
+    // only ever meet if they are congruent modulo the drift, and the further apart they are the
+    // more passes it takes for the lower one to catch up, so the spread of the first pass bounds
+    // how long the search can take. This doesn't lean on `first_repeat`, so cycling through that
+    // many passes checks it independently.
+    pub fn analyze(&self) -> Result<Analysis<T>, RepeatError<T>> {
+        if self.changes.is_empty() {
+            return Err(RepeatError::Empty);
+        }
+
+        let (sums, drift) = self.starting_points()?;
+        if drift == T::zero() {
+            return Ok(Analysis { drift, passes: 1 });
+        }
+
+        let mut residues = HashSet::new();
+        if sums.iter().all(|sum| residues.insert(sum.residue(&drift))) {
+            return Err(RepeatError::NoRepeat { drift });
+        }
+
+        let min = sums.iter().min().unwrap();
+        let max = sums.iter().max().unwrap();
+        // Frequencies too far apart to count the passes between in a usize get a bound that's
+        // still true, just not a useful one
+        let passes = min
+            .drifts_between(max, &drift)
+            .map_or(usize::MAX, |passes| passes.saturating_add(1));
+        Ok(Analysis { drift, passes })
+    }
+
+    // As in `analyze`, two frequencies can only ever meet if they are congruent modulo the drift,
+    // so the first pass is enough: the frequencies in each residue class are sorted in the
+    // direction of the drift; each one is next reached by its predecessor in that order, after
+    // (difference / drift) passes. The earliest of those catch-ups, ordered by pass and then by
+    // position in the pass, is the first repeat.
+    pub fn first_repeat(&self) -> Result<Repeat<T>, RepeatError<T>> {
+        if self.changes.is_empty() {
+            return Err(RepeatError::Empty);
+        }
+
+        let (sums, drift) = self.starting_points()?;
+        if drift == T::zero() {
+            let mut seen = HashMap::new();
+            for (i, sum) in sums.iter().enumerate() {
+                if let Some(first) = seen.insert(sum, i) {
+                    return Ok(self.repeat_at(sum.clone(), 0, i, first));
+                }
+            }
+            return Ok(self.repeat_at(T::zero(), 1, 0, 0));
+        }
+
+        let climbing = drift > T::zero();
+        let mut classes: HashMap<T, Vec<(&T, usize)>> = HashMap::new();
+        for (i, sum) in sums.iter().enumerate() {
+            classes
+                .entry(sum.residue(&drift))
+                .or_default()
+                .push((sum, i));
+        }
+
+        // (passes, position, frequency, first position)
+        let mut first: Option<(usize, usize, &T, usize)> = None;
+        let mut too_far = false;
+        for class in classes.values_mut() {
+            class.sort_unstable_by(|a, b| match a.0.cmp(b.0) {
+                Ordering::Equal => a.1.cmp(&b.1),
+                ordering if climbing => ordering,
+                ordering => ordering.reverse(),
+            });
+            for pair in class.windows(2) {
+                let (low, low_pos) = pair[0];
+                let (high, high_pos) = pair[1];
+                let passes = match low.drifts_between(high, &drift) {
+                    Some(passes) => passes,
+                    None => {
+                        too_far = true;
+                        continue;
+                    }
+                };
+                let candidate = if passes == 0 {
+                    (passes, high_pos, high, low_pos)
+                } else {
+                    (passes, low_pos, high, high_pos)
+                };
+                if first.is_none_or(|first| (candidate.0, candidate.1) < (first.0, first.1)) {
+                    first = Some(candidate);
+                }
+            }
+        }
+
+        match first {
+            Some((passes, position, frequency, first)) => {
+                // Keep the step number countable too
+                if passes.checked_mul(self.changes.len()).is_none() {
+                    return Err(RepeatError::TooFar);
+                }
+                Ok(self.repeat_at(frequency.clone(), passes, position, first))
+            }
+            None if too_far => Err(RepeatError::TooFar),
+            None => Err(RepeatError::NoRepeat { drift }),
+        }
+    }
+
+    // Position `position` of pass `passes` is reached by the change just before it.
+    fn repeat_at(&self, frequency: T, passes: usize, position: usize, first: usize) -> Repeat<T> {
+        let len = self.changes.len();
+        let (cycle, index) = if position == 0 {
+            (passes - 1, len - 1)
+        } else {
+            (passes, position - 1)
+        };
+        Repeat {
+            frequency,
+            cycle,
+            index,
+            first_step: first,
+        }
+    }
+
+    pub fn first_repeat_by_cycling(&self) -> Result<Repeat<T>, RepeatError<T>> {
+        let analysis = self.analyze()?;
+
+        let len = self.changes.len();
+        let mut sum = T::zero();
+        let mut seen = HashMap::new();
+        seen.insert(sum.clone(), 0);
+        for cycle in 0..analysis.passes {
+            // Same limit as `first_repeat`: the step number has to be countable
+            let start = cycle.checked_mul(len).ok_or(RepeatError::TooFar)?;
+            for (index, change) in self.changes.iter().enumerate() {
+                sum = sum
+                    .checked_add(change)
+                    .ok_or_else(|| self.overflow_at(index))?;
+                if let Some(first_step) = seen.insert(sum.clone(), start + index + 1) {
+                    return Ok(Repeat {
+                        frequency: sum,
+                        cycle,
+                        index,
+                        first_step,
+                    });
+                }
+            }
+        }
+        unreachable!(
+            "analysis promised a repeat within {} passes",
+            analysis.passes
+        )
+    }
+}
+
+impl<T> From<Vec<T>> for Frequencies<T> {
+    fn from(changes: Vec<T>) -> Frequencies<T> {
+        let lines = (1..=changes.len()).collect();
+        Frequencies { changes, lines }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Analysis<T = isize> {
+    // How far the frequency moves after one pass through the changes
+    pub drift: T,
+    // A frequency repeats within this many passes
+    pub passes: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Repeat<T = isize> {
+    pub frequency: T,
+    // Pass through the changes (counting from 0) in which the frequency came back
+    pub cycle: usize,
+    // Index of the change that brought it back
+    pub index: usize,
+    // Number of changes applied when it was first reached (0 is the starting frequency)
+    pub first_step: usize,
+}
+
+impl<T> Repeat<T> {
+    pub fn step(&self, len: usize) -> usize {
+        self.cycle * len + self.index + 1
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    // The whole line, as read
+    pub text: String,
+    pub reason: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {} in {:?}",
+            self.line, self.column, self.reason, self.text
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overflow {
+    // Line of the change that overflowed the running frequency
+    pub line: usize,
+}
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "frequency overflows at line {}", self.line)
+    }
+}
+
+impl Error for Overflow {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepeatError<T = isize> {
+    Empty,
+    NoRepeat { drift: T },
+    Overflow(Overflow),
+    // The repeat is further away than a usize can count
+    TooFar,
+}
+
+impl<T> From<Overflow> for RepeatError<T> {
+    fn from(overflow: Overflow) -> RepeatError<T> {
+        RepeatError::Overflow(overflow)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for RepeatError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RepeatError::Empty => write!(f, "no frequency changes to apply"),
+            RepeatError::NoRepeat { drift } => {
+                write!(f, "frequency never repeats (drifts by {} each pass)", drift)
+            }
+            RepeatError::Overflow(overflow) => overflow.fmt(f),
+            RepeatError::TooFar => write!(f, "frequency repeats too many passes away to count"),
+        }
+    }
+}
+
+impl<T: fmt::Debug + fmt::Display> Error for RepeatError<T> {}
+
+// xorshift, so the randomized tests don't need rand. The closure returns numbers below its bound.
+#[cfg(test)]
+fn xorshift(seed: u64) -> impl FnMut(u64) -> u64 {
+    let mut state = seed;
+    move |bound| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state % bound
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_read() {
+    let frequencies = Frequencies::<isize>::read("+1\n-2\n+3\n+1\n".as_bytes()).unwrap();
+    assert_eq!(frequencies.changes(), &[1, -2, 3, 1]);
+    assert_eq!(frequencies.sum(), Ok(3));
+    assert!(Frequencies::<isize>::read("+1\nfoo\n".as_bytes()).is_err());
+}
+
+#[cfg(test)]
+#[test]
+fn test_read_lenient() {
+    let frequencies = Frequencies::<isize>::read("  +1, -2\n\n\t+3 ,\n+1\n".as_bytes()).unwrap();
+    assert_eq!(frequencies.changes(), &[1, -2, 3, 1]);
+
+    let err = Frequencies::<isize>::read("+1\n\n+2, +x3\n".as_bytes()).unwrap_err();
+    assert_eq!(
+        err.downcast::<ParseError>().unwrap(),
+        ParseError {
+            line: 3,
+            column: 5,
+            text: "+2, +x3".to_string(),
+            reason: "invalid digit found in string".to_string(),
+        }
+    );
+
+    let frequencies =
+        Frequencies::<isize>::read(format!("+1\n\n{}, +1\n", isize::MAX).as_bytes()).unwrap();
+    assert_eq!(frequencies.sum(), Err(Overflow { line: 3 }));
+}
+
+#[cfg(test)]
+#[test]
+fn test_analyze() {
+    let analyze = |changes: &[isize]| Frequencies::from(changes.to_vec()).analyze();
+    assert_eq!(analyze(&[]), Err(RepeatError::Empty));
+    assert_eq!(analyze(&[1]), Err(RepeatError::NoRepeat { drift: 1 }));
+    assert_eq!(analyze(&[-2, -1]), Err(RepeatError::NoRepeat { drift: -3 }));
+    assert_eq!(
+        analyze(&[1, -1]),
+        Ok(Analysis {
+            drift: 0,
+            passes: 1
+        })
+    );
+    assert_eq!(
+        analyze(&[3, 3, 4, -2, -4]),
+        Ok(Analysis {
+            drift: 4,
+            passes: 3
+        })
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_first_repeat() {
+    let first_repeat = |changes: &[isize]| {
+        Frequencies::from(changes.to_vec())
+            .first_repeat()
+            .map(|r| r.frequency)
+    };
+    assert_eq!(first_repeat(&[]), Err(RepeatError::Empty));
+    assert_eq!(first_repeat(&[1, -1]), Ok(0));
+    assert_eq!(first_repeat(&[3, 3, 4, -2, -4]), Ok(10));
+    assert_eq!(first_repeat(&[-6, 3, 8, 5, -6]), Ok(5));
+    assert_eq!(first_repeat(&[7, 7, -2, -7, -4]), Ok(14));
+    assert_eq!(
+        first_repeat(&[1, 2, 3]),
+        Err(RepeatError::NoRepeat { drift: 6 })
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_repeat_diagnostics() {
+    // 0, +3 = 3, +3 = 6, +4 = 10, -2 = 8, -4 = 4, +3 = 7, +3 = 10
+    assert_eq!(
+        Frequencies::<isize>::from(vec![3, 3, 4, -2, -4]).first_repeat(),
+        Ok(Repeat {
+            frequency: 10,
+            cycle: 1,
+            index: 1,
+            first_step: 3,
+        })
+    );
+    // 0, +1 = 1, -1 = 0
+    assert_eq!(
+        Frequencies::<isize>::from(vec![1, -1]).first_repeat(),
+        Ok(Repeat {
+            frequency: 0,
+            cycle: 0,
+            index: 1,
+            first_step: 0,
+        })
+    );
+    // 0, +1 = 1, +1 = 2, -1 = 1
+    assert_eq!(
+        Frequencies::<isize>::from(vec![1, 1, -1]).first_repeat(),
+        Ok(Repeat {
+            frequency: 1,
+            cycle: 0,
+            index: 2,
+            first_step: 1,
+        })
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_first_repeat_matches_cycling() {
+    let mut next = xorshift(0x2545_f491_4f6c_dd1d_u64);
+
+    for _ in 0..5000 {
+        let len = next(20) as usize + 1;
+        let spread = next(100) as isize + 1;
+        let frequencies = (0..len)
+            .map(|_| next(2 * spread as u64 + 1) as isize - spread)
+            .collect::<Vec<_>>();
+        let frequencies = Frequencies::from(frequencies);
+        assert_eq!(
+            frequencies.first_repeat(),
+            frequencies.first_repeat_by_cycling(),
+            "{:?}",
+            frequencies
+        );
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_overflow() {
+    let frequencies = Frequencies::from(vec![1, isize::MAX, -2]);
+    assert_eq!(frequencies.sum(), Err(Overflow { line: 2 }));
+    assert_eq!(
+        frequencies.first_repeat(),
+        Err(RepeatError::Overflow(Overflow { line: 2 }))
+    );
+
+    // Sums that stay in range but are too far apart to subtract in an isize
+    let frequencies = Frequencies::from(vec![isize::MAX, isize::MIN, 2]);
+    assert_eq!(frequencies.sum(), Ok(1));
+    assert_eq!(frequencies.first_repeat().map(|r| r.frequency), Ok(0));
+}
+
+#[cfg(feature = "bigint")]
+#[test]
+fn test_bigint() {
+    use num_bigint::BigInt;
+
+    // Climbs one past i128::MAX before falling back
+    let input =
+        "+170141183460469231731687303715884105727\n+1\n-170141183460469231731687303715884105729\n";
+    let frequencies = Frequencies::<BigInt>::read(input.as_bytes()).unwrap();
+    assert_eq!(frequencies.sum(), Ok(BigInt::from(-1)));
+    let repeat = frequencies.first_repeat().unwrap();
+    assert_eq!(Ok(repeat.clone()), frequencies.first_repeat_by_cycling());
+    assert_eq!(repeat.frequency, BigInt::from(i128::MAX));
+    assert_eq!(repeat.step(3), 5);
+
+    let frequencies = Frequencies::from(vec![i128::MAX, 1]);
+    assert_eq!(frequencies.sum(), Err(Overflow { line: 2 }));
+}
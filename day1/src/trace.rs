@@ -0,0 +1,209 @@
+use crate::{Frequencies, Frequency, Overflow};
+use failure::Fallible;
+use std::collections::HashSet;
+use std::io::Write;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Fallible<Format> {
+        match s {
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            s => Err(failure::format_err!("unknown trace format {:?}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step<T = isize> {
+    // Number of changes applied so far; step 0 is the starting frequency
+    pub step: usize,
+    pub cycle: usize,
+    // The change applied to get here, and its index in the list
+    pub index: Option<usize>,
+    pub change: Option<T>,
+    pub frequency: T,
+    pub first_visit: bool,
+    // Set on the step that answers part 2
+    pub first_repeat: bool,
+}
+
+pub struct Trace<'a, T> {
+    frequencies: &'a Frequencies<T>,
+    cycles: usize,
+    step: usize,
+    frequency: T,
+    seen: HashSet<T>,
+    repeated: bool,
+    overflowed: bool,
+}
+
+impl<'a, T: Frequency> Iterator for Trace<'a, T> {
+    type Item = Result<Step<T>, Overflow>;
+
+    fn next(&mut self) -> Option<Result<Step<T>, Overflow>> {
+        let len = self.frequencies.changes().len();
+        if self.overflowed || self.step > len.saturating_mul(self.cycles) {
+            return None;
+        }
+
+        let (cycle, index, change) = if self.step == 0 {
+            (0, None, None)
+        } else {
+            let index = (self.step - 1) % len;
+            let change = &self.frequencies.changes()[index];
+            self.frequency = match self.frequency.checked_add(change) {
+                Some(frequency) => frequency,
+                None => {
+                    // Nothing sensible comes after an overflow
+                    self.overflowed = true;
+                    return Some(Err(self.frequencies.overflow_at(index)));
+                }
+            };
+            ((self.step - 1) / len, Some(index), Some(change.clone()))
+        };
+
+        let first_visit = self.seen.insert(self.frequency.clone());
+        let first_repeat = !first_visit && !self.repeated;
+        self.repeated |= first_repeat;
+        let step = Step {
+            step: self.step,
+            cycle,
+            index,
+            change,
+            frequency: self.frequency.clone(),
+            first_visit,
+            first_repeat,
+        };
+        self.step += 1;
+        Some(Ok(step))
+    }
+}
+
+impl<T: Frequency> Frequencies<T> {
+    pub fn trace(&self, cycles: usize) -> Trace<'_, T> {
+        Trace {
+            frequencies: self,
+            cycles,
+            step: 0,
+            frequency: T::zero(),
+            seen: HashSet::new(),
+            repeated: false,
+            overflowed: false,
+        }
+    }
+
+    pub fn write_trace<W: Write>(
+        &self,
+        mut writer: W,
+        format: Format,
+        cycles: usize,
+    ) -> Fallible<()> {
+        match format {
+            Format::Csv => writeln!(
+                writer,
+                "step,cycle,index,change,frequency,first_visit,first_repeat"
+            )?,
+            Format::Json => writeln!(writer, "[")?,
+        }
+
+        for (i, step) in self.trace(cycles).enumerate() {
+            let step = step?;
+            let index = step.index.map(|i| i.to_string());
+            let change = step.change.as_ref().map(T::to_string);
+            match format {
+                Format::Csv => writeln!(
+                    writer,
+                    "{},{},{},{},{},{},{}",
+                    step.step,
+                    step.cycle,
+                    index.unwrap_or_default(),
+                    change.unwrap_or_default(),
+                    step.frequency,
+                    step.first_visit,
+                    step.first_repeat
+                )?,
+                Format::Json => write!(
+                    writer,
+                    "{}  {{\"step\": {}, \"cycle\": {}, \"index\": {}, \"change\": {}, \
+                     \"frequency\": {}, \"first_visit\": {}, \"first_repeat\": {}}}",
+                    if i == 0 { "" } else { ",\n" },
+                    step.step,
+                    step.cycle,
+                    index.as_ref().map_or("null", String::as_str),
+                    change.as_ref().map_or("null", String::as_str),
+                    step.frequency,
+                    step.first_visit,
+                    step.first_repeat
+                )?,
+            }
+        }
+
+        if format == Format::Json {
+            writeln!(writer, "\n]")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_trace() {
+    let frequencies = Frequencies::<isize>::from(vec![1, -2, 3]);
+    let trace = frequencies
+        .trace(2)
+        .map(|step| {
+            let step = step.unwrap();
+            (step.frequency, step.first_visit, step.first_repeat)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        trace,
+        vec![
+            (0, true, false),
+            (1, true, false),
+            (-1, true, false),
+            (2, true, false),
+            (3, true, false),
+            (1, false, true),
+            (4, true, false),
+        ]
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_write_trace() {
+    let frequencies = Frequencies::<isize>::from(vec![1, -1]);
+
+    let mut csv = Vec::new();
+    frequencies.write_trace(&mut csv, Format::Csv, 1).unwrap();
+    assert_eq!(
+        String::from_utf8(csv).unwrap(),
+        "step,cycle,index,change,frequency,first_visit,first_repeat\n\
+         0,0,,,0,true,false\n\
+         1,0,0,1,1,true,false\n\
+         2,0,1,-1,0,false,true\n"
+    );
+
+    let mut json = Vec::new();
+    frequencies.write_trace(&mut json, Format::Json, 1).unwrap();
+    assert_eq!(
+        String::from_utf8(json).unwrap(),
+        "[\n  \
+         {\"step\": 0, \"cycle\": 0, \"index\": null, \"change\": null, \"frequency\": 0, \
+         \"first_visit\": true, \"first_repeat\": false},\n  \
+         {\"step\": 1, \"cycle\": 0, \"index\": 0, \"change\": 1, \"frequency\": 1, \
+         \"first_visit\": true, \"first_repeat\": false},\n  \
+         {\"step\": 2, \"cycle\": 0, \"index\": 1, \"change\": -1, \"frequency\": 0, \
+         \"first_visit\": false, \"first_repeat\": true}\n]\n"
+    );
+}
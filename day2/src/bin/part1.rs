@@ -1,29 +1,41 @@
-use std::io::{self, BufRead, BufReader};
+use std::env;
+use std::io::{self, BufReader};
 
-fn checksum<R: BufRead>(reader: R) -> io::Result<u64> {
-    let mut twos = 0;
-    let mut threes = 0;
+fn usage() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "usage: part1 [--threads N] [K...]",
+    )
+}
 
-    for line in reader.lines() {
-        let line = line?;
-        let mut count = [0; 26];
-        for c in line.chars() {
-            let pos = (c as u32) - ('a' as u32);
-            let pos = pos as usize;
-            count[pos] += 1;
-        }
-        if count.iter().any(|n| *n == 2) {
-            twos += 1;
-        }
-        if count.iter().any(|n| *n == 3) {
-            threes += 1;
+fn main() -> io::Result<()> {
+    // Optionally count other letter frequencies than 2 and 3
+    let mut ks = Vec::new();
+    let mut threads = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--threads" {
+            threads = Some(args.next().and_then(|n| n.parse().ok()).ok_or_else(usage)?);
+        } else {
+            ks.push(arg.parse().map_err(|_| usage())?);
         }
     }
+    if ks.is_empty() {
+        ks = vec![2, 3];
+    }
 
-    Ok(twos * threes)
-}
+    // Both paths count raw lines, so they agree on input that isn't a list of valid box IDs
+    let checksum = match threads {
+        Some(threads) => day2::checksum_parallel(io::stdin(), &ks, threads)?,
+        None => day2::checksum_with(BufReader::new(io::stdin()), &ks)?,
+    };
 
-fn main() -> io::Result<()> {
-    println!("{}", checksum(BufReader::new(io::stdin()))?);
+    for (k, n) in &checksum.counts {
+        eprintln!("{} IDs with a letter exactly {} times", n, k);
+    }
+    let product = checksum
+        .product()
+        .ok_or_else(|| io::Error::other("the checksum doesn't fit in a u64"))?;
+    println!("{}", product);
     Ok(())
 }
@@ -1,28 +1,45 @@
-use std::io::{self, BufRead, BufReader};
+use day2::{IdFormat, Metric};
+use std::env;
+use std::io::{self, BufReader};
 
-fn similar(a: &str, b: &str) -> Option<String> {
-    let c: String = a
-        .chars()
-        .zip(b.chars())
-        .filter_map(|(a, b)| if a == b { Some(a) } else { None })
-        .collect();
-    if c.len() == a.len() - 1 {
-        Some(c)
-    } else {
-        None
-    }
+fn usage() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "usage: part2 [--hamming N | --levenshtein N] [--cluster]",
+    )
 }
 
 fn main() -> io::Result<()> {
-    let mut lines = BufReader::new(io::stdin())
-        .lines()
-        .collect::<Result<Vec<_>, _>>()?;
-    while let Some(a) = lines.pop() {
-        for b in lines.iter() {
-            if let Some(c) = similar(&a, &b) {
-                println!("{}", c);
+    let mut metric = None;
+    let mut cluster = false;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut max = || args.next().and_then(|n| n.parse().ok()).ok_or_else(usage);
+        match arg.as_str() {
+            "--hamming" => metric = Some(Metric::Hamming(max()?)),
+            "--levenshtein" => metric = Some(Metric::Levenshtein(max()?)),
+            "--cluster" => cluster = true,
+            _ => return Err(usage()),
+        }
+    }
+
+    let ids = IdFormat::default().read(BufReader::new(io::stdin()))?;
+    if cluster {
+        for cluster in day2::clusters(&ids, metric.unwrap_or(Metric::Hamming(1))) {
+            println!("{}", cluster.consensus);
+            for id in cluster.ids {
+                println!("  {}", id);
             }
         }
+    } else if let Some(metric) = metric {
+        for m in day2::matches(&ids, metric) {
+            println!("distance {}\n{}\n", m.distance, m.alignment);
+        }
+    } else {
+        for pair in day2::near_duplicates(&ids) {
+            println!("{}", pair.common);
+            eprintln!("{} and {} differ at {}", pair.a, pair.b, pair.index);
+        }
     }
 
     Ok(())
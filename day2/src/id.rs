@@ -0,0 +1,229 @@
+use crate::{Alignment, Metric};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead};
+use std::ops::Deref;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alphabet {
+    // a to z, as in the puzzle
+    Lowercase,
+    // ASCII letters and digits
+    Alphanumeric,
+    // Anything but whitespace and control characters
+    Printable,
+}
+
+impl Alphabet {
+    pub fn contains(self, c: char) -> bool {
+        match self {
+            Alphabet::Lowercase => c.is_ascii_lowercase(),
+            Alphabet::Alphanumeric => c.is_ascii_alphanumeric(),
+            Alphabet::Printable => !c.is_whitespace() && !c.is_control(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdFormat {
+    pub alphabet: Alphabet,
+    // Length in chars every ID must have, if any
+    pub len: Option<usize>,
+}
+
+impl IdFormat {
+    pub fn parse(&self, s: &str) -> Result<BoxId, BoxIdError> {
+        if s.is_empty() {
+            return Err(BoxIdError::Empty);
+        }
+        if let Some((index, c)) = s
+            .chars()
+            .enumerate()
+            .find(|(_, c)| !self.alphabet.contains(*c))
+        {
+            return Err(BoxIdError::BadChar { c, index });
+        }
+        if let Some(expected) = self.len {
+            let found = s.chars().count();
+            if found != expected {
+                return Err(BoxIdError::BadLength { expected, found });
+            }
+        }
+        Ok(BoxId(s.to_string()))
+    }
+
+    pub fn read<R: BufRead>(&self, reader: R) -> io::Result<Vec<BoxId>> {
+        reader
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                self.parse(&line?).map_err(|err| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("line {}: {}", i + 1, err),
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+impl Default for IdFormat {
+    fn default() -> IdFormat {
+        IdFormat {
+            alphabet: Alphabet::Printable,
+            len: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoxIdError {
+    Empty,
+    BadChar { c: char, index: usize },
+    BadLength { expected: usize, found: usize },
+}
+
+impl fmt::Display for BoxIdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BoxIdError::Empty => write!(f, "box ID is empty"),
+            BoxIdError::BadChar { c, index } => {
+                write!(f, "box ID has {:?} at position {}", c, index)
+            }
+            BoxIdError::BadLength { expected, found } => {
+                write!(f, "box ID is {} letters long, expected {}", found, expected)
+            }
+        }
+    }
+}
+
+impl Error for BoxIdError {}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BoxId(String);
+
+impl BoxId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn letter_counts(&self) -> HashMap<char, usize> {
+        crate::letter_counts(&self.0)
+    }
+
+    // The letters in common if the two IDs differ in exactly one position
+    pub fn similar(&self, other: &BoxId) -> Option<String> {
+        crate::similar(&self.0, &other.0)
+    }
+
+    pub fn align(&self, other: &BoxId, metric: Metric) -> Option<Alignment> {
+        metric.align(&self.0, &other.0)
+    }
+
+    pub fn distance(&self, other: &BoxId, metric: Metric) -> Option<usize> {
+        self.align(other, metric)
+            .map(|alignment| alignment.distance())
+    }
+}
+
+impl FromStr for BoxId {
+    type Err = BoxIdError;
+
+    fn from_str(s: &str) -> Result<BoxId, BoxIdError> {
+        IdFormat::default().parse(s)
+    }
+}
+
+impl Deref for BoxId {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for BoxId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for BoxId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_box_id_parse() {
+    assert_eq!(
+        "abcdef".parse::<BoxId>().map(|id| id.to_string()),
+        Ok("abcdef".to_string())
+    );
+    assert_eq!("Ünï1".parse::<BoxId>().map(|id| id.chars().count()), Ok(4));
+    assert_eq!("".parse::<BoxId>(), Err(BoxIdError::Empty));
+    assert_eq!(
+        "ab cd".parse::<BoxId>(),
+        Err(BoxIdError::BadChar { c: ' ', index: 2 })
+    );
+
+    let format = IdFormat {
+        alphabet: Alphabet::Lowercase,
+        len: Some(5),
+    };
+    assert!(format.parse("fghij").is_ok());
+    assert_eq!(
+        format.parse("fgHij"),
+        Err(BoxIdError::BadChar { c: 'H', index: 2 })
+    );
+    assert_eq!(
+        format.parse("fghijk"),
+        Err(BoxIdError::BadLength {
+            expected: 5,
+            found: 6
+        })
+    );
+
+    let err = format.read("abcde\nfghij\nklm\n".as_bytes()).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(
+        err.to_string(),
+        "line 3: box ID is 3 letters long, expected 5"
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_box_id_letters() {
+    // The examples from part 1
+    let letters = |id: &str| {
+        let counts = id.parse::<BoxId>().unwrap().letter_counts();
+        let has = |k| counts.values().any(|n| *n == k);
+        (has(2), has(3))
+    };
+    assert_eq!(letters("abcdef"), (false, false));
+    assert_eq!(letters("bababc"), (true, true));
+    assert_eq!(letters("abbcde"), (true, false));
+    assert_eq!(letters("abcccd"), (false, true));
+    assert_eq!(letters("aabcdd"), (true, false));
+    assert_eq!(letters("abcdee"), (true, false));
+    assert_eq!(letters("ababab"), (false, true));
+}
+
+#[cfg(test)]
+#[test]
+fn test_box_id_compare() {
+    // The examples from part 2
+    let ids = IdFormat::default()
+        .read("abcde\nfghij\nklmno\npqrst\nfguij\naxcye\nwvxyz\n".as_bytes())
+        .unwrap();
+    assert_eq!(ids[0].similar(&ids[5]), None);
+    assert_eq!(ids[0].distance(&ids[5], Metric::Hamming(2)), Some(2));
+    assert_eq!(ids[1].similar(&ids[4]), Some("fgij".to_string()));
+    assert_eq!(ids[1].distance(&ids[4], Metric::Hamming(1)), Some(1));
+    assert_eq!(crate::near_duplicates(&ids)[0].common, "fgij");
+}
@@ -0,0 +1,583 @@
+mod id;
+mod parallel;
+
+pub use crate::id::{Alphabet, BoxId, BoxIdError, IdFormat};
+pub use crate::parallel::checksum_parallel;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufRead};
+
+pub fn letter_counts(id: &str) -> HashMap<char, usize> {
+    let mut counts = HashMap::new();
+    for c in id.chars() {
+        *counts.entry(c).or_insert(0) += 1;
+    }
+    counts
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checksum {
+    // For each k, how many IDs have some letter exactly k times
+    pub counts: Vec<(usize, u64)>,
+}
+
+impl Checksum {
+    pub fn new(ks: &[usize]) -> Checksum {
+        Checksum {
+            counts: ks.iter().map(|k| (*k, 0)).collect(),
+        }
+    }
+
+    pub fn add(&mut self, id: &str) {
+        let counts = letter_counts(id);
+        for (k, n) in self.counts.iter_mut() {
+            if counts.values().any(|count| count == k) {
+                *n += 1;
+            }
+        }
+    }
+
+    pub fn merge(&mut self, other: &Checksum) {
+        for ((k, n), (other_k, other_n)) in self.counts.iter_mut().zip(&other.counts) {
+            assert_eq!(k, other_k, "merging checksums of different counts");
+            *n += other_n;
+        }
+    }
+
+    // None if the product doesn't fit in a u64, which repeated or many k's can cause
+    pub fn product(&self) -> Option<u64> {
+        self.counts
+            .iter()
+            .try_fold(1u64, |product, (_, n)| product.checked_mul(*n))
+    }
+}
+
+pub fn checksum<R: BufRead>(reader: R) -> io::Result<Checksum> {
+    checksum_with(reader, &[2, 3])
+}
+
+pub fn checksum_with<R: BufRead>(reader: R, ks: &[usize]) -> io::Result<Checksum> {
+    let mut checksum = Checksum::new(ks);
+    for line in reader.lines() {
+        checksum.add(&line?);
+    }
+    Ok(checksum)
+}
+
+pub fn similar(a: &str, b: &str) -> Option<String> {
+    if a.chars().count() != b.chars().count() {
+        return None;
+    }
+    let c: String = a
+        .chars()
+        .zip(b.chars())
+        .filter_map(|(a, b)| if a == b { Some(a) } else { None })
+        .collect();
+    if c.chars().count() + 1 == a.chars().count() {
+        Some(c)
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    // Substitutions only, between IDs of the same length, up to the given distance
+    Hamming(usize),
+    // Substitutions, insertions and deletions, up to the given distance
+    Levenshtein(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edit {
+    Same(char),
+    Substitute(char, char),
+    Insert(char),
+    Delete(char),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alignment(pub Vec<Edit>);
+
+impl Alignment {
+    pub fn distance(&self) -> usize {
+        self.0
+            .iter()
+            .filter(|edit| !matches!(edit, Edit::Same(_)))
+            .count()
+    }
+}
+
+// Three rows: the first ID, a bar under each matching letter, and the second ID, with gaps
+// marked by '-'.
+impl fmt::Display for Alignment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (mut top, mut middle, mut bottom) = (String::new(), String::new(), String::new());
+        for edit in &self.0 {
+            let (a, m, b) = match *edit {
+                Edit::Same(c) => (c, '|', c),
+                Edit::Substitute(a, b) => (a, ' ', b),
+                Edit::Insert(b) => ('-', ' ', b),
+                Edit::Delete(a) => (a, ' ', '-'),
+            };
+            top.push(a);
+            middle.push(m);
+            bottom.push(b);
+        }
+        write!(f, "{}\n{}\n{}", top, middle, bottom)
+    }
+}
+
+impl Metric {
+    pub fn max_distance(self) -> usize {
+        match self {
+            Metric::Hamming(max) | Metric::Levenshtein(max) => max,
+        }
+    }
+
+    // The cheapest way to turn `a` into `b`, if it is within the maximum distance
+    pub fn align(self, a: &str, b: &str) -> Option<Alignment> {
+        let a = a.chars().collect::<Vec<_>>();
+        let b = b.chars().collect::<Vec<_>>();
+        let alignment = match self {
+            Metric::Hamming(_) => {
+                if a.len() != b.len() {
+                    return None;
+                }
+                Alignment(
+                    a.iter()
+                        .zip(&b)
+                        .map(|(&a, &b)| {
+                            if a == b {
+                                Edit::Same(a)
+                            } else {
+                                Edit::Substitute(a, b)
+                            }
+                        })
+                        .collect(),
+                )
+            }
+            Metric::Levenshtein(max) => {
+                if a.len().max(b.len()) - a.len().min(b.len()) > max {
+                    return None;
+                }
+                levenshtein(&a, &b)
+            }
+        };
+        if alignment.distance() <= self.max_distance() {
+            Some(alignment)
+        } else {
+            None
+        }
+    }
+}
+
+fn levenshtein(a: &[char], b: &[char]) -> Alignment {
+    // cost[i][j] is the distance between a[..i] and b[..j]
+    let mut cost = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in cost.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, c) in cost[0].iter_mut().enumerate() {
+        *c = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitute = cost[i - 1][j - 1] + if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            cost[i][j] = substitute.min(cost[i - 1][j] + 1).min(cost[i][j - 1] + 1);
+        }
+    }
+
+    let mut edits = Vec::new();
+    let (mut i, mut j) = (a.len(), b.len());
+    while i > 0 || j > 0 {
+        if i > 0 && j > 0 {
+            let same = a[i - 1] == b[j - 1];
+            if cost[i][j] == cost[i - 1][j - 1] + if same { 0 } else { 1 } {
+                edits.push(if same {
+                    Edit::Same(a[i - 1])
+                } else {
+                    Edit::Substitute(a[i - 1], b[j - 1])
+                });
+                i -= 1;
+                j -= 1;
+                continue;
+            }
+        }
+        if i > 0 && cost[i][j] == cost[i - 1][j] + 1 {
+            edits.push(Edit::Delete(a[i - 1]));
+            i -= 1;
+        } else {
+            edits.push(Edit::Insert(b[j - 1]));
+            j -= 1;
+        }
+    }
+    edits.reverse();
+    Alignment(edits)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match<'a> {
+    pub a: &'a str,
+    pub b: &'a str,
+    pub distance: usize,
+    pub alignment: Alignment,
+}
+
+// Compares every pair of IDs; identical IDs are not reported.
+pub fn matches<S: AsRef<str>>(ids: &[S], metric: Metric) -> Vec<Match<'_>> {
+    let mut found = Vec::new();
+    for (i, a) in ids.iter().enumerate() {
+        for b in &ids[i + 1..] {
+            let (a, b) = (a.as_ref(), b.as_ref());
+            if let Some(alignment) = metric.align(a, b) {
+                let distance = alignment.distance();
+                if distance > 0 {
+                    found.push(Match {
+                        a,
+                        b,
+                        distance,
+                        alignment,
+                    });
+                }
+            }
+        }
+    }
+    found
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NearDuplicate<'a> {
+    pub a: &'a str,
+    pub b: &'a str,
+    // Position (in chars) of the one letter that differs
+    pub index: usize,
+    pub common: String,
+}
+
+// For each position, IDs that are identical once that position is masked out land in the same
+// bucket, and any two of them with different letters at that position differ there alone.
+pub fn near_duplicates<S: AsRef<str>>(ids: &[S]) -> Vec<NearDuplicate<'_>> {
+    let chars = ids
+        .iter()
+        .map(|id| id.as_ref().chars().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let longest = chars.iter().map(Vec::len).max().unwrap_or(0);
+
+    let mut found = Vec::new();
+    for index in 0..longest {
+        let mut buckets: HashMap<String, Vec<usize>> = HashMap::new();
+        for (i, id) in chars.iter().enumerate() {
+            if index < id.len() {
+                let masked = id[..index].iter().chain(&id[index + 1..]).collect();
+                buckets.entry(masked).or_default().push(i);
+            }
+        }
+
+        for (common, bucket) in buckets {
+            for (n, &a) in bucket.iter().enumerate() {
+                for &b in &bucket[n + 1..] {
+                    if chars[a][index] != chars[b][index] {
+                        found.push((a, b, index, common.clone()));
+                    }
+                }
+            }
+        }
+    }
+
+    found.sort_unstable();
+    found
+        .into_iter()
+        .map(|(a, b, index, common)| NearDuplicate {
+            a: ids[a].as_ref(),
+            b: ids[b].as_ref(),
+            index,
+            common,
+        })
+        .collect()
+}
+
+struct DisjointSet {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(n: usize) -> DisjointSet {
+        DisjointSet {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+        }
+    }
+
+    fn find(&mut self, i: usize) -> usize {
+        let mut root = i;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        let mut i = i;
+        while self.parent[i] != root {
+            i = std::mem::replace(&mut self.parent[i], root);
+        }
+        root
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            let (small, large) = if self.size[a] < self.size[b] {
+                (a, b)
+            } else {
+                (b, a)
+            };
+            self.parent[small] = large;
+            self.size[large] += self.size[small];
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cluster<'a> {
+    pub ids: Vec<&'a str>,
+    pub consensus: String,
+}
+
+// The letters every ID in the group shares. Under Hamming distance that is every position where
+// they all agree; under Levenshtein distance each ID in turn is aligned against what is left.
+fn consensus(ids: &[&str], metric: Metric) -> String {
+    match metric {
+        Metric::Hamming(_) => {
+            let chars = ids
+                .iter()
+                .map(|id| id.chars().collect::<Vec<_>>())
+                .collect::<Vec<_>>();
+            (0..chars[0].len())
+                .filter(|&i| chars.iter().all(|id| id[i] == chars[0][i]))
+                .map(|i| chars[0][i])
+                .collect()
+        }
+        Metric::Levenshtein(_) => ids[1..].iter().fold(ids[0].to_string(), |common, id| {
+            let common = common.chars().collect::<Vec<_>>();
+            let id = id.chars().collect::<Vec<_>>();
+            levenshtein(&common, &id)
+                .0
+                .into_iter()
+                .filter_map(|edit| match edit {
+                    Edit::Same(c) => Some(c),
+                    _ => None,
+                })
+                .collect()
+        }),
+    }
+}
+
+// Groups IDs that are linked by a chain of pairs within the metric's distance. IDs that are not
+// within reach of any other ID are left out.
+pub fn clusters<S: AsRef<str>>(ids: &[S], metric: Metric) -> Vec<Cluster<'_>> {
+    let mut set = DisjointSet::new(ids.len());
+    for (i, a) in ids.iter().enumerate() {
+        for (j, b) in ids.iter().enumerate().skip(i + 1) {
+            if metric.align(a.as_ref(), b.as_ref()).is_some() {
+                set.union(i, j);
+            }
+        }
+    }
+
+    let mut groups: Vec<Vec<&str>> = Vec::new();
+    let mut group_of = HashMap::new();
+    for (i, id) in ids.iter().enumerate() {
+        let root = set.find(i);
+        let group = *group_of.entry(root).or_insert_with(|| {
+            groups.push(Vec::new());
+            groups.len() - 1
+        });
+        groups[group].push(id.as_ref());
+    }
+
+    groups
+        .into_iter()
+        .filter(|ids| ids.len() > 1)
+        .map(|ids| Cluster {
+            consensus: consensus(&ids, metric),
+            ids,
+        })
+        .collect()
+}
+
+// xorshift, so the randomized tests don't need rand. The closure returns numbers below its bound.
+#[cfg(test)]
+fn xorshift(seed: u64) -> impl FnMut(u64) -> u64 {
+    let mut state = seed;
+    move |bound| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state % bound
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_checksum() {
+    let input = "abcdef\nbababc\nabbcde\nabcccd\naabcdd\nabcdee\nababab\n";
+    let checksum = checksum(input.as_bytes()).unwrap();
+    assert_eq!(checksum.counts, vec![(2, 4), (3, 3)]);
+    assert_eq!(checksum.product(), Some(12));
+
+    let checksum = checksum_with("Ünïcödë\nÜÜ11\naaaa\n".as_bytes(), &[1, 2, 4]).unwrap();
+    assert_eq!(checksum.counts, vec![(1, 1), (2, 1), (4, 1)]);
+    assert_eq!(checksum.product(), Some(1));
+
+    let mut checksum = Checksum::new(&[1, 1, 1, 1]);
+    checksum.counts.iter_mut().for_each(|(_, n)| *n = 100_000);
+    assert_eq!(checksum.product(), None);
+}
+
+#[cfg(test)]
+#[test]
+fn test_near_duplicates() {
+    let ids = [
+        "abcde", "fghij", "klmno", "pqrst", "fguij", "axcye", "wvxyz",
+    ];
+    assert_eq!(
+        near_duplicates(&ids),
+        vec![NearDuplicate {
+            a: "fghij",
+            b: "fguij",
+            index: 2,
+            common: "fgij".to_string(),
+        }]
+    );
+
+    // Duplicates and IDs of different lengths never match
+    assert!(near_duplicates(&["abc", "abc", "abcd", "ab"]).is_empty());
+}
+
+#[cfg(test)]
+#[test]
+fn test_near_duplicates_matches_similar() {
+    let mut next = xorshift(0x9e37_79b9_7f4a_7c15_u64);
+
+    for _ in 0..200 {
+        let ids = (0..next(40))
+            .map(|_| {
+                (0..4)
+                    .map(|_| (b'a' + next(3) as u8) as char)
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut expected = Vec::new();
+        for (i, a) in ids.iter().enumerate() {
+            for b in &ids[i + 1..] {
+                if let Some(common) = similar(a, b) {
+                    expected.push((a.as_str(), b.as_str(), common));
+                }
+            }
+        }
+        expected.sort();
+
+        let mut found = near_duplicates(&ids)
+            .into_iter()
+            .map(|d| (d.a, d.b, d.common))
+            .collect::<Vec<_>>();
+        found.sort();
+        assert_eq!(found, expected);
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_similar_lengths() {
+    assert_eq!(similar("fghij", "fguij"), Some("fgij".to_string()));
+    assert_eq!(similar("abcd", "abc"), None);
+    assert_eq!(similar("abc", "abcd"), None);
+    assert_eq!(similar("äbc", "äbd"), Some("äb".to_string()));
+}
+
+#[cfg(test)]
+#[test]
+fn test_align() {
+    let hamming = Metric::Hamming(2);
+    assert_eq!(hamming.align("abcde", "axcye").unwrap().distance(), 2);
+    assert_eq!(hamming.align("abcde", "axcyz"), None);
+    assert_eq!(hamming.align("abcde", "abcd"), None);
+    assert_eq!(
+        hamming.align("fghij", "fguij").unwrap().to_string(),
+        "fghij\n|| ||\nfguij"
+    );
+
+    let levenshtein = Metric::Levenshtein(2);
+    assert_eq!(levenshtein.align("kitten", "sitting"), None);
+    let alignment = Metric::Levenshtein(3).align("kitten", "sitting").unwrap();
+    assert_eq!(alignment.distance(), 3);
+    assert_eq!(alignment.to_string(), "kitten-\n ||| | \nsitting");
+    let alignment = levenshtein.align("abcde", "bcdef").unwrap();
+    assert_eq!(
+        alignment.0,
+        vec![
+            Edit::Delete('a'),
+            Edit::Same('b'),
+            Edit::Same('c'),
+            Edit::Same('d'),
+            Edit::Same('e'),
+            Edit::Insert('f'),
+        ]
+    );
+    assert_eq!(levenshtein.align("abcdef", "abc"), None);
+}
+
+#[cfg(test)]
+#[test]
+fn test_matches() {
+    let ids = ["abcde", "fghij", "fguij", "fghijk", "abcde"];
+    let found = matches(&ids, Metric::Hamming(1))
+        .into_iter()
+        .map(|m| (m.a, m.b, m.distance))
+        .collect::<Vec<_>>();
+    assert_eq!(found, vec![("fghij", "fguij", 1)]);
+
+    let found = matches(&ids, Metric::Levenshtein(2))
+        .into_iter()
+        .map(|m| (m.a, m.b, m.distance))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        vec![
+            ("fghij", "fguij", 1),
+            ("fghij", "fghijk", 1),
+            ("fguij", "fghijk", 2),
+        ]
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_clusters() {
+    let ids = [
+        "abcde", "fghij", "abcdx", "fguij", "abxdx", "klmno", "fguix",
+    ];
+    assert_eq!(
+        clusters(&ids, Metric::Hamming(1)),
+        vec![
+            Cluster {
+                ids: vec!["abcde", "abcdx", "abxdx"],
+                consensus: "abd".to_string(),
+            },
+            Cluster {
+                ids: vec!["fghij", "fguij", "fguix"],
+                consensus: "fgi".to_string(),
+            },
+        ]
+    );
+
+    let ids = ["abcde", "abcd", "xabcd", "zzzzz"];
+    assert_eq!(
+        clusters(&ids, Metric::Levenshtein(1)),
+        vec![Cluster {
+            ids: vec!["abcde", "abcd", "xabcd"],
+            consensus: "abcd".to_string(),
+        }]
+    );
+}
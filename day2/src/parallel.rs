@@ -0,0 +1,107 @@
+use crate::Checksum;
+use std::io::{self, Read};
+use std::mem;
+use std::str;
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::thread;
+
+const CHUNK_SIZE: usize = 4 << 20;
+
+// Reads the input in chunks that end on a line boundary and hands them to a pool of threads, each
+// keeping its own counts until the input runs out.
+pub fn checksum_parallel<R: Read>(reader: R, ks: &[usize], threads: usize) -> io::Result<Checksum> {
+    checksum_chunked(reader, ks, threads, CHUNK_SIZE)
+}
+
+fn checksum_chunked<R: Read>(
+    mut reader: R,
+    ks: &[usize],
+    threads: usize,
+    chunk_size: usize,
+) -> io::Result<Checksum> {
+    let (sender, receiver) = mpsc::sync_channel::<Vec<u8>>(threads);
+    let receiver = Mutex::new(receiver);
+
+    thread::scope(|scope| {
+        let workers = (0..threads.max(1))
+            .map(|_| {
+                scope.spawn(|| {
+                    // Keep draining after a bad chunk so the reader never blocks on a full channel
+                    let mut checksum = Ok(Checksum::new(ks));
+                    loop {
+                        let chunk = match receiver.lock().unwrap().recv() {
+                            Ok(chunk) => chunk,
+                            Err(_) => return checksum,
+                        };
+                        if let Ok(counts) = &mut checksum {
+                            match str::from_utf8(&chunk) {
+                                Ok(chunk) => chunk.lines().for_each(|line| counts.add(line)),
+                                Err(err) => {
+                                    checksum = Err(io::Error::new(io::ErrorKind::InvalidData, err))
+                                }
+                            }
+                        }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut buf = vec![0; chunk_size];
+        let mut carry = Vec::new();
+        let read = loop {
+            let n = match reader.read(&mut buf) {
+                Ok(0) => {
+                    if !carry.is_empty() {
+                        sender.send(carry).unwrap();
+                    }
+                    break Ok(());
+                }
+                Ok(n) => n,
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => break Err(err),
+            };
+            carry.extend_from_slice(&buf[..n]);
+            if let Some(end) = buf[..n].iter().rposition(|b| *b == b'\n') {
+                let rest = carry.split_off(carry.len() - n + end + 1);
+                sender.send(mem::replace(&mut carry, rest)).unwrap();
+            }
+        };
+        drop(sender);
+
+        let mut total = Checksum::new(ks);
+        for worker in workers {
+            total.merge(&worker.join().unwrap()?);
+        }
+        read.map(|()| total)
+    })
+}
+
+#[cfg(test)]
+#[test]
+fn test_checksum_parallel() {
+    let mut next = crate::xorshift(0x853c_49e6_748f_ea9b_u64);
+
+    let mut input = String::new();
+    for _ in 0..2000 {
+        for _ in 0..next(12) {
+            input.push(['a', 'b', 'c', 'd', 'é', 'ß'][next(6) as usize]);
+        }
+        input.push_str(["\n", "\r\n"][next(2) as usize]);
+    }
+    input.push_str("aabbb");
+    let ks = [1, 2, 3, 4];
+
+    let serial = crate::checksum_with(input.as_bytes(), &ks).unwrap();
+    for &(threads, chunk_size) in &[(1, 1), (3, 7), (4, 64), (8, CHUNK_SIZE)] {
+        assert_eq!(
+            checksum_chunked(input.as_bytes(), &ks, threads, chunk_size).unwrap(),
+            serial
+        );
+    }
+    assert_eq!(
+        checksum_parallel(&b""[..], &ks, 4).unwrap(),
+        crate::checksum_with(&b""[..], &ks).unwrap()
+    );
+    assert!(checksum_parallel(&b"ab\n\xff\n"[..], &ks, 2).is_err());
+}
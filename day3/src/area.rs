@@ -0,0 +1,95 @@
+use crate::{Claim, Coord};
+use std::convert::TryFrom;
+
+// Sweeps across x over the compressed y coordinates, so the work depends on the number of claims
+// rather than on how big the fabric is. Only square inches inside some claim are counted, so a
+// `min_claims` of 0 gives the same area as 1.
+pub fn covered_area<T: Coord>(claims: &[Claim<T>], min_claims: u32) -> u128 {
+    let min_claims = usize::try_from(min_claims.max(1)).unwrap_or(usize::MAX);
+    let mut ys = claims
+        .iter()
+        .filter(|claim| !claim.is_empty())
+        .flat_map(|claim| vec![claim.y_range().start, claim.y_range().end])
+        .collect::<Vec<_>>();
+    ys.sort_unstable();
+    ys.dedup();
+    let slot = |y| ys.binary_search(&y).unwrap();
+
+    let mut events = Vec::new();
+    for claim in claims.iter().filter(|claim| !claim.is_empty()) {
+        events.push((claim.x_range().start, 1, claim));
+        events.push((claim.x_range().end, -1, claim));
+    }
+    events.sort_unstable_by_key(|(x, delta, _)| (*x, *delta));
+
+    // counts[i] is the number of open claims covering ys[i]..ys[i + 1]
+    let mut counts = vec![0_usize; ys.len().saturating_sub(1)];
+    let mut area = 0;
+    let mut last_x = None;
+    for (x, delta, claim) in events {
+        let covered = counts
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| **n >= min_claims)
+            .map(|(i, _)| (ys[i + 1].wide() - ys[i].wide()) as u128)
+            .sum::<u128>();
+        area += covered * (x.wide() - last_x.unwrap_or(x).wide()) as u128;
+        last_x = Some(x);
+
+        let range = claim.y_range();
+        for n in &mut counts[slot(range.start)..slot(range.end)] {
+            // Every claim is opened before it's closed, so this never goes below 0
+            *n = n.wrapping_add_signed(delta);
+        }
+    }
+    area
+}
+
+// Square inches within at least one claim
+pub fn union_area<T: Coord>(claims: &[Claim<T>]) -> u128 {
+    covered_area(claims, 1)
+}
+
+// Square inches within two or more claims
+pub fn overlapping_area<T: Coord>(claims: &[Claim<T>]) -> u128 {
+    covered_area(claims, 2)
+}
+
+#[cfg(test)]
+#[test]
+fn test_area() {
+    let claims = crate::testing::example_claims();
+    assert_eq!(overlapping_area(&claims), 4);
+    assert_eq!(union_area(&claims), 32);
+    assert_eq!(covered_area(&claims, 3), 0);
+    assert_eq!(covered_area(&claims, u32::MAX), 0);
+    assert_eq!(covered_area(&claims, 0), 32);
+    assert_eq!(union_area::<u16>(&[]), 0);
+
+    // Far apart claims only cost as much as their corners
+    let claims = ["#1 @ 0,0: 60000x60000", "#2 @ 50000,50000: 10000x10000"]
+        .iter()
+        .map(|s| s.parse().unwrap())
+        .collect::<Vec<Claim>>();
+    assert_eq!(union_area(&claims), 3_600_000_000);
+    assert_eq!(overlapping_area(&claims), 100_000_000);
+}
+
+#[cfg(test)]
+#[test]
+fn test_area_matches_fabric() {
+    let mut next = crate::testing::xorshift(0x2545_f491_4f6c_dd1d_u64);
+
+    for _ in 0..200 {
+        let claims = crate::testing::random_claims(&mut next, 30, 40, 0..10);
+        let fabric = crate::Fabric::from_claims(&claims).unwrap();
+        assert_eq!(overlapping_area(&claims), fabric.overlapping() as u128);
+        for k in 1..4 {
+            let expected = (0..50)
+                .flat_map(|x| (0..50).map(move |y| (x, y)))
+                .filter(|&(x, y)| fabric.coverage(x, y) >= k)
+                .count();
+            assert_eq!(covered_area(&claims, k), expected as u128, "{:?}", claims);
+        }
+    }
+}
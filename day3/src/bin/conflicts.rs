@@ -0,0 +1,46 @@
+use day3::{Claim, ConflictGraph};
+use failure::Fallible;
+use std::env;
+use std::io::{self, BufReader, BufWriter};
+
+fn main() -> Fallible<()> {
+    let mut format = "summary".to_string();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                format = args
+                    .next()
+                    .ok_or_else(|| failure::format_err!("{} needs a value", arg))?
+            }
+            _ => failure::bail!("usage: conflicts [--format summary|dot|json]"),
+        }
+    }
+
+    let claims: Vec<Claim> = day3::read_claims(BufReader::new(io::stdin()), |line, err| {
+        eprintln!("line {}: {}", line, err)
+    })?;
+    let graph = ConflictGraph::new(&claims);
+    let stdout = io::stdout();
+    match format.as_str() {
+        "summary" => {
+            let components = graph.components();
+            println!(
+                "{} conflicts, {} groups, largest has {} claims",
+                graph.conflicts().len(),
+                components.len(),
+                components.first().map_or(0, Vec::len)
+            );
+            for claim in graph.most_conflicted(10) {
+                println!(
+                    "#{}: {} conflicts over {} square inches",
+                    claim.id, claim.conflicts, claim.area
+                );
+            }
+        }
+        "dot" => graph.write_dot(BufWriter::new(stdout.lock()))?,
+        "json" => graph.write_json(BufWriter::new(stdout.lock()))?,
+        format => failure::bail!("unknown graph format {:?}", format),
+    }
+    Ok(())
+}
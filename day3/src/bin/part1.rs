@@ -1,21 +1,13 @@
-use day3::Claim;
+use day3::{Claim, Fabric};
 use failure::Fallible;
-use std::collections::HashMap;
-use std::io::{self, BufRead, BufReader};
+use std::io::{self, BufReader};
 
 fn main() -> Fallible<()> {
-    let mut fabric = HashMap::new();
+    let claims: Vec<Claim> = day3::read_claims(BufReader::new(io::stdin()), |line, err| {
+        eprintln!("line {}: {}", line, err)
+    })?;
 
-    for line in BufReader::new(io::stdin()).lines() {
-        let claim: Claim = line?.parse()?;
-        for x in claim.x_range() {
-            for y in claim.y_range() {
-                *fabric.entry((x, y)).or_insert(0) += 1;
-            }
-        }
-    }
-
-    println!("{}", fabric.values().filter(|v| **v >= 2).count());
+    println!("{}", Fabric::from_claims(&claims)?.overlapping());
 
     Ok(())
 }
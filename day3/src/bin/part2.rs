@@ -1,19 +1,14 @@
 use day3::Claim;
 use failure::Fallible;
-use std::io::{self, BufRead, BufReader};
+use std::io::{self, BufReader};
 
 fn main() -> Fallible<()> {
-    let mut claims = BufReader::new(io::stdin())
-        .lines()
-        .map(|line| -> Fallible<Claim> { line?.parse().map_err(failure::Error::from) })
-        .collect::<Result<Vec<_>, _>>()?;
-    let mut checked = Vec::new();
+    let claims: Vec<Claim> = day3::read_claims(BufReader::new(io::stdin()), |line, err| {
+        eprintln!("line {}: {}", line, err)
+    })?;
 
-    while let Some(a) = claims.pop() {
-        if claims.iter().chain(checked.iter()).all(|b| !a.overlap(b)) {
-            println!("{}", a.id);
-        }
-        checked.push(a);
+    for claim in day3::intact_claims(&claims) {
+        println!("{}", claim.id);
     }
 
     Ok(())
@@ -0,0 +1,61 @@
+use day3::{Claim, ClaimIndex};
+use failure::Fallible;
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+fn ids(claims: Vec<&Claim>) -> String {
+    if claims.is_empty() {
+        return "none".to_string();
+    }
+    claims
+        .iter()
+        .map(|claim| claim.id.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn answer(index: &ClaimIndex, query: &str) -> Fallible<String> {
+    let words = query.split_whitespace().collect::<Vec<_>>();
+    let numbers = words
+        .iter()
+        .skip(1)
+        .map(|word| word.parse::<u16>())
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(match (words.first(), &numbers[..]) {
+        (Some(&"at"), &[x, y]) => ids(index.at(x, y)),
+        (Some(&"within"), &[x, y, width, height]) => {
+            let rect = Claim::new(0, x, y, width, height)
+                .ok_or_else(|| failure::format_err!("rectangle is out of range"))?;
+            ids(index.within(rect.x_range(), rect.y_range()))
+        }
+        (Some(&"free"), &[x, y]) => match index.nearest_free(x, y) {
+            Some((x, y)) => format!("{},{}", x, y),
+            None => "none".to_string(),
+        },
+        _ => failure::bail!("queries are: at X Y, within X Y WIDTH HEIGHT, free X Y"),
+    })
+}
+
+fn main() -> Fallible<()> {
+    let path = match env::args().nth(1) {
+        Some(path) => path,
+        None => failure::bail!("usage: query CLAIMS < QUERIES"),
+    };
+    let claims = day3::read_claims(BufReader::new(File::open(path)?), |line, err| {
+        eprintln!("line {}: {}", line, err)
+    })?;
+    let index = ClaimIndex::new(claims);
+
+    for query in BufReader::new(io::stdin()).lines() {
+        let query = query?;
+        if query.trim().is_empty() {
+            continue;
+        }
+        match answer(&index, &query) {
+            Ok(answer) => println!("{}", answer),
+            Err(err) => eprintln!("{}: {}", query.trim(), err),
+        }
+    }
+    Ok(())
+}
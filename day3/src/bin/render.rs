@@ -0,0 +1,27 @@
+use day3::{Claim, ImageFormat, Picture};
+use failure::Fallible;
+use std::env;
+use std::io::{self, BufReader, BufWriter};
+
+fn main() -> Fallible<()> {
+    let mut format = ImageFormat::Ascii;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                format = args
+                    .next()
+                    .ok_or_else(|| failure::format_err!("{} needs a value", arg))?
+                    .parse()?
+            }
+            _ => failure::bail!("usage: render [--format ascii|pgm|ppm]"),
+        }
+    }
+
+    let claims: Vec<Claim> = day3::read_claims(BufReader::new(io::stdin()), |line, err| {
+        eprintln!("line {}: {}", line, err)
+    })?;
+    let stdout = io::stdout();
+    Picture::new(&claims)?.write(BufWriter::new(stdout.lock()), format)?;
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use std::convert::TryFrom;
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+use std::num::ParseIntError;
+use std::ops::Sub;
+use std::str::FromStr;
+
+// The integer type claim coordinates and sizes are given in. Sizes are never negative, and a claim
+// has to end at or before the largest value the type holds; differences and areas are worked out in
+// `i128` so they can't overflow.
+pub trait Coord:
+    Copy + Ord + Hash + Default + Debug + Display + FromStr<Err = ParseIntError> + Sub<Output = Self>
+{
+    const MIN: Self;
+    const MAX: Self;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+
+    fn saturating_add(self, other: Self) -> Self;
+
+    fn wide(self) -> i128;
+
+    fn from_wide(wide: i128) -> Option<Self>;
+}
+
+macro_rules! impl_coord {
+    ($($t:ty)*) => {$(
+        impl Coord for $t {
+            const MIN: $t = <$t>::MIN;
+            const MAX: $t = <$t>::MAX;
+
+            fn checked_add(self, other: $t) -> Option<$t> {
+                <$t>::checked_add(self, other)
+            }
+
+            fn saturating_add(self, other: $t) -> $t {
+                <$t>::saturating_add(self, other)
+            }
+
+            fn wide(self) -> i128 {
+                i128::from(self)
+            }
+
+            fn from_wide(wide: i128) -> Option<$t> {
+                <$t>::try_from(wide).ok()
+            }
+        }
+    )*};
+}
+
+impl_coord!(i16 i32 i64 u8 u16 u32 u64);
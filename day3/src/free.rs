@@ -0,0 +1,223 @@
+use crate::{grid, len, Claim, Coord, GridTooLarge};
+use std::convert::TryFrom;
+use std::ops::Range;
+
+// Which square inches inside a bound are still free once the claims are placed. Rectangles handed
+// back are claims with ID 0.
+#[derive(Debug, Clone)]
+pub struct FreeSpace<T = u16> {
+    xs: Range<T>,
+    ys: Range<T>,
+    width: usize,
+    height: usize,
+    // covered[y * width + x], where x and y count from the corner of the bound
+    covered: Vec<bool>,
+    // sums[y * (width + 1) + x] counts the covered cells above and left of (x, y)
+    sums: Vec<usize>,
+}
+
+impl<T: Coord> FreeSpace<T> {
+    pub fn new(
+        claims: &[Claim<T>],
+        xs: Range<T>,
+        ys: Range<T>,
+    ) -> Result<FreeSpace<T>, GridTooLarge> {
+        let (width, height) = (len(&xs), len(&ys));
+        let mut covered = grid(width, height, false)?;
+        for claim in claims {
+            let (cxs, cys) = (claim.x_range(), claim.y_range());
+            let clip = |inner: &Range<T>, outer: &Range<T>| {
+                len(&(outer.start..inner.start.max(outer.start)))
+                    ..len(&(outer.start..inner.end.min(outer.end)))
+            };
+            let (x0, x1) = (clip(&cxs, &xs).start, clip(&cxs, &xs).end);
+            for y in clip(&cys, &ys) {
+                for cell in covered[y * width..(y + 1) * width]
+                    .iter_mut()
+                    .take(x1)
+                    .skip(x0)
+                {
+                    *cell = true;
+                }
+            }
+        }
+
+        let stride = width.saturating_add(1);
+        let mut sums = grid(stride, height.saturating_add(1), 0)?;
+        for y in 0..height {
+            for x in 0..width {
+                sums[(y + 1) * stride + x + 1] =
+                    sums[y * stride + x + 1] + sums[(y + 1) * stride + x] - sums[y * stride + x]
+                        + covered[y * width + x] as usize;
+            }
+        }
+
+        Ok(FreeSpace {
+            xs,
+            ys,
+            width,
+            height,
+            covered,
+            sums,
+        })
+    }
+
+    fn rect(&self, x: usize, y: usize, width: usize, height: usize) -> Claim<T> {
+        let at = |start: T, offset: usize| T::from_wide(start.wide() + offset as i128).unwrap();
+        Claim {
+            id: 0,
+            x: at(self.xs.start, x),
+            y: at(self.ys.start, y),
+            width: T::from_wide(width as i128).unwrap(),
+            height: T::from_wide(height as i128).unwrap(),
+        }
+    }
+
+    // The free rectangle with the largest area. Goes row by row keeping, for each column, how many
+    // free cells stack up above it, and finds the largest rectangle under that skyline with a stack.
+    // A claim can't be wider or taller than the largest coordinate, so with signed coordinates a
+    // wide bound can hold free space bigger than any claim; sides are capped at that size.
+    pub fn largest(&self) -> Option<Claim<T>> {
+        let cap = usize::try_from(T::MAX.wide()).unwrap_or(usize::MAX);
+        let mut heights = vec![0; self.width];
+        // (area, x, y, width, height)
+        let mut best = (0, 0, 0, 0, 0);
+        for y in 0..self.height {
+            for (x, height) in heights.iter_mut().enumerate() {
+                *height = if self.covered[y * self.width + x] {
+                    0
+                } else {
+                    *height + 1
+                };
+            }
+
+            // Columns whose skyline is still rising, as (first column, height)
+            let mut stack: Vec<(usize, usize)> = Vec::new();
+            for x in 0..=self.width {
+                let height = heights.get(x).cloned().unwrap_or(0);
+                let mut start = x;
+                while let Some(&(left, top)) = stack.last() {
+                    if top < height {
+                        break;
+                    }
+                    stack.pop();
+                    let (width, height) = ((x - left).min(cap), top.min(cap));
+                    if width * height > best.0 {
+                        best = (width * height, left, y + 1 - top, width, height);
+                    }
+                    start = left;
+                }
+                stack.push((start, height));
+            }
+        }
+        let (area, x, y, width, height) = best;
+        if area == 0 {
+            None
+        } else {
+            Some(self.rect(x, y, width, height))
+        }
+    }
+
+    // Where a `width` by `height` claim could go without covering anything, topmost then leftmost
+    pub fn fit(&self, width: T, height: T) -> Option<Claim<T>> {
+        let (width, height) = (
+            usize::try_from(width.wide()).ok()?,
+            usize::try_from(height.wide()).ok()?,
+        );
+        if width > self.width || height > self.height {
+            return None;
+        }
+
+        let (sums, stride) = (&self.sums, self.width + 1);
+        let covered = |x: usize, y: usize| {
+            sums[(y + height) * stride + x + width] + sums[y * stride + x]
+                - sums[y * stride + x + width]
+                - sums[(y + height) * stride + x]
+        };
+        (0..=self.height - height)
+            .flat_map(|y| (0..=self.width - width).map(move |x| (x, y)))
+            .find(|&(x, y)| covered(x, y) == 0)
+            .map(|(x, y)| self.rect(x, y, width, height))
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_free_space() {
+    let claims = crate::testing::example_claims();
+    let space = FreeSpace::new(&claims, 0..8, 0..8).unwrap();
+    let largest = space.largest().unwrap();
+    assert_eq!(largest, Claim::new(0, 0, 0, 3, 3).unwrap());
+    assert_eq!(space.fit(4, 3), None);
+    assert_eq!(space.fit(3, 3), Some(largest));
+    assert_eq!(space.fit(8, 1), Some(Claim::new(0, 0, 0, 8, 1).unwrap()));
+    assert_eq!(space.fit(1, 3), Some(Claim::new(0, 0, 0, 1, 3).unwrap()));
+    assert_eq!(space.fit(1, 8), Some(Claim::new(0, 0, 0, 1, 8).unwrap()));
+    assert_eq!(space.fit(2, 5), None);
+    assert_eq!(space.fit(9, 1), None);
+    assert_eq!(space.fit(0, 0), Some(Claim::new(0, 0, 0, 0, 0).unwrap()));
+
+    // Claims poking out of the bound are clipped to it
+    let space = FreeSpace::new(&claims, 4..6, 2..4).unwrap();
+    assert_eq!(space.largest(), None);
+    let space = FreeSpace::new(&claims, 7..10, 0..3).unwrap();
+    assert_eq!(space.largest(), Some(Claim::new(0, 7, 0, 3, 3).unwrap()));
+    assert_eq!(
+        FreeSpace::<u16>::new(&[], 3..3, 0..5).unwrap().largest(),
+        None
+    );
+    assert!(FreeSpace::<u64>::new(&[], 0..u64::MAX, 0..u64::MAX).is_err());
+    assert!(FreeSpace::<u64>::new(&[], 0..u64::MAX, 0..0).is_err());
+
+    // Free space wider than the largest coordinate gives the widest claim there is
+    let space = FreeSpace::<i16>::new(&[], -20000..20000, 0..1).unwrap();
+    assert_eq!(
+        space.largest(),
+        Some(Claim::new(0, -20000, 0, i16::MAX, 1).unwrap())
+    );
+    assert_eq!(
+        space.fit(i16::MAX, 1),
+        Some(Claim::new(0, -20000, 0, i16::MAX, 1).unwrap())
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_free_space_matches_brute_force() {
+    let mut next = crate::testing::xorshift(0xbb67_ae85_84ca_a73b_u64);
+
+    for _ in 0..100 {
+        let claims = crate::testing::random_claims(&mut next, 12, 20, 0..8);
+        let (x0, y0) = (next(5) as u16, next(5) as u16);
+        let (xs, ys) = (x0..x0 + next(16) as u16, y0..y0 + next(16) as u16);
+        let space = FreeSpace::new(&claims, xs.clone(), ys.clone()).unwrap();
+
+        let free = |rect: &Claim| claims.iter().all(|claim| !claim.overlap(rect));
+        let mut best = 0;
+        for w in 1..=xs.len() as u16 {
+            for h in 1..=ys.len() as u16 {
+                let expected = ys
+                    .clone()
+                    .flat_map(|y| xs.clone().map(move |x| (x, y)))
+                    .map(|(x, y)| Claim::new(0, x, y, w, h).unwrap())
+                    .find(|rect| {
+                        rect.x_range().end <= xs.end && rect.y_range().end <= ys.end && free(rect)
+                    });
+                assert_eq!(space.fit(w, h), expected);
+                if expected.is_some() {
+                    best = best.max(w * h);
+                }
+            }
+        }
+
+        match space.largest() {
+            Some(rect) => {
+                assert!(free(&rect));
+                assert!(xs.start <= rect.x && rect.x_range().end <= xs.end);
+                assert!(ys.start <= rect.y && rect.y_range().end <= ys.end);
+                assert_eq!(rect.width * rect.height, best);
+            }
+            None => assert_eq!(best, 0),
+        }
+    }
+}
@@ -0,0 +1,255 @@
+use crate::{Claim, Coord};
+use std::io::{self, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Conflict {
+    pub a: u64,
+    pub b: u64,
+    // Square inches both claims want
+    pub area: u128,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Conflicted {
+    pub id: u64,
+    pub conflicts: usize,
+    // Summed over every claim it conflicts with, so inches shared three ways count twice
+    pub area: u128,
+}
+
+// Claims are nodes, kept in input order; an edge joins every pair of overlapping claims
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConflictGraph {
+    ids: Vec<u64>,
+    conflicts: Vec<Conflict>,
+    adjacency: Vec<Vec<(usize, u128)>>,
+}
+
+impl ConflictGraph {
+    pub fn new<T: Coord>(claims: &[Claim<T>]) -> ConflictGraph {
+        let mut adjacency = vec![Vec::new(); claims.len()];
+        let mut conflicts = Vec::new();
+
+        // Only claims that start before this one ends along x can overlap it
+        let mut order = (0..claims.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&i| claims[i].x_range().start);
+        for (n, &i) in order.iter().enumerate() {
+            let end = claims[i].x_range().end;
+            for &j in order[n + 1..]
+                .iter()
+                .take_while(|&&j| claims[j].x_range().start < end)
+            {
+                if let Some(both) = claims[i].intersection(&claims[j]) {
+                    let area = both.width.wide() as u128 * both.height.wide() as u128;
+                    let (i, j) = (i.min(j), i.max(j));
+                    adjacency[i].push((j, area));
+                    adjacency[j].push((i, area));
+                    conflicts.push((i, j, area));
+                }
+            }
+        }
+        conflicts.sort_unstable();
+        for neighbours in &mut adjacency {
+            neighbours.sort_unstable();
+        }
+
+        ConflictGraph {
+            ids: claims.iter().map(|claim| claim.id).collect(),
+            conflicts: conflicts
+                .into_iter()
+                .map(|(i, j, area)| Conflict {
+                    a: claims[i].id,
+                    b: claims[j].id,
+                    area,
+                })
+                .collect(),
+            adjacency,
+        }
+    }
+
+    pub fn conflicts(&self) -> &[Conflict] {
+        &self.conflicts
+    }
+
+    // Groups of claims linked by conflicts, largest first; an intact claim is a group by itself
+    pub fn components(&self) -> Vec<Vec<u64>> {
+        let mut seen = vec![false; self.ids.len()];
+        let mut components = Vec::new();
+        for start in 0..self.ids.len() {
+            if seen[start] {
+                continue;
+            }
+            seen[start] = true;
+            let mut component = Vec::new();
+            let mut stack = vec![start];
+            while let Some(i) = stack.pop() {
+                component.push(i);
+                for &(j, _) in &self.adjacency[i] {
+                    if !seen[j] {
+                        seen[j] = true;
+                        stack.push(j);
+                    }
+                }
+            }
+            component.sort_unstable();
+            components.push(component);
+        }
+        components.sort_by_key(|component| std::cmp::Reverse(component.len()));
+        components
+            .into_iter()
+            .map(|component| component.into_iter().map(|i| self.ids[i]).collect())
+            .collect()
+    }
+
+    // The `n` claims with the most conflicts, ties broken by contested area
+    pub fn most_conflicted(&self, n: usize) -> Vec<Conflicted> {
+        let mut conflicted = self
+            .adjacency
+            .iter()
+            .zip(&self.ids)
+            .map(|(neighbours, id)| Conflicted {
+                id: *id,
+                conflicts: neighbours.len(),
+                area: neighbours.iter().map(|(_, area)| area).sum(),
+            })
+            .collect::<Vec<_>>();
+        conflicted.sort_by(|a, b| {
+            (b.conflicts, b.area)
+                .cmp(&(a.conflicts, a.area))
+                .then(a.id.cmp(&b.id))
+        });
+        conflicted.truncate(n);
+        conflicted
+    }
+
+    pub fn write_dot<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "graph conflicts {{")?;
+        for id in &self.ids {
+            writeln!(writer, "  {};", id)?;
+        }
+        for conflict in &self.conflicts {
+            writeln!(
+                writer,
+                "  {} -- {} [label={}, weight={}];",
+                conflict.a, conflict.b, conflict.area, conflict.area
+            )?;
+        }
+        writeln!(writer, "}}")
+    }
+
+    pub fn write_json<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "[")?;
+        for (i, (id, neighbours)) in self.ids.iter().zip(&self.adjacency).enumerate() {
+            let neighbours = neighbours
+                .iter()
+                .map(|(j, area)| format!("{{\"id\": {}, \"area\": {}}}", self.ids[*j], area))
+                .collect::<Vec<_>>();
+            writeln!(
+                writer,
+                "  {{\"id\": {}, \"conflicts\": [{}]}}{}",
+                id,
+                neighbours.join(", "),
+                if i + 1 == self.ids.len() { "" } else { "," }
+            )?;
+        }
+        writeln!(writer, "]")
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_conflict_graph() {
+    let mut claims = crate::testing::example_claims();
+    claims.extend(
+        ["#4 @ 0,0: 2x2", "#5 @ 4,6: 2x1"]
+            .iter()
+            .map(|s| s.parse::<Claim>().unwrap()),
+    );
+    let graph = ConflictGraph::new(&claims);
+    assert_eq!(
+        graph.conflicts(),
+        &[
+            Conflict {
+                a: 1,
+                b: 2,
+                area: 4
+            },
+            Conflict {
+                a: 1,
+                b: 5,
+                area: 1
+            },
+            Conflict {
+                a: 3,
+                b: 5,
+                area: 1
+            },
+        ]
+    );
+    assert_eq!(graph.components(), vec![vec![1, 2, 3, 5], vec![4]]);
+    assert_eq!(
+        graph.most_conflicted(3),
+        vec![
+            Conflicted {
+                id: 1,
+                conflicts: 2,
+                area: 5
+            },
+            Conflicted {
+                id: 5,
+                conflicts: 2,
+                area: 2
+            },
+            Conflicted {
+                id: 2,
+                conflicts: 1,
+                area: 4
+            },
+        ]
+    );
+
+    let mut dot = Vec::new();
+    ConflictGraph::new(&claims[..2])
+        .write_dot(&mut dot)
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(dot).unwrap(),
+        "graph conflicts {\n  1;\n  2;\n  1 -- 2 [label=4, weight=4];\n}\n"
+    );
+
+    let mut json = Vec::new();
+    ConflictGraph::new(&claims[2..])
+        .write_json(&mut json)
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(json).unwrap(),
+        "[\n  \
+         {\"id\": 3, \"conflicts\": [{\"id\": 5, \"area\": 1}]},\n  \
+         {\"id\": 4, \"conflicts\": []},\n  \
+         {\"id\": 5, \"conflicts\": [{\"id\": 3, \"area\": 1}]}\n]\n"
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_conflict_graph_matches_pairs() {
+    let mut next = crate::testing::xorshift(0x9e37_79b9_7f4a_7c15_u64);
+
+    for _ in 0..200 {
+        let claims = crate::testing::random_claims(&mut next, 30, 40, 0..10);
+        let mut expected = Vec::new();
+        for (i, a) in claims.iter().enumerate() {
+            for b in &claims[i + 1..] {
+                if a.overlap(b) {
+                    let area = crate::overlapping_area(&[*a, *b]);
+                    expected.push(Conflict {
+                        a: a.id,
+                        b: b.id,
+                        area,
+                    });
+                }
+            }
+        }
+        assert_eq!(ConflictGraph::new(&claims).conflicts(), &expected[..]);
+    }
+}
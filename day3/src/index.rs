@@ -0,0 +1,283 @@
+use crate::{Claim, Coord, RangeExt};
+use std::collections::HashMap;
+use std::ops::Range;
+
+// How many tiles a claim may be bucketed into before it's kept aside instead
+const MAX_TILES: i128 = 64;
+
+// Claims bucketed into square tiles about the size of a typical claim, so a query only looks at the
+// claims sharing a tile with it. A claim much bigger than the rest would fill a huge number of
+// tiles, so those are kept in a list every query goes through.
+#[derive(Debug, Clone)]
+pub struct ClaimIndex<T = u16> {
+    claims: Vec<Claim<T>>,
+    tile: i128,
+    tiles: HashMap<(i128, i128), Vec<usize>>,
+    large: Vec<usize>,
+}
+
+impl<T: Coord> ClaimIndex<T> {
+    pub fn new(claims: Vec<Claim<T>>) -> ClaimIndex<T> {
+        let sizes = claims
+            .iter()
+            .filter(|claim| !claim.is_empty())
+            .map(|claim| claim.width.wide().max(claim.height.wide()))
+            .collect::<Vec<_>>();
+        let mean = sizes.iter().sum::<i128>() / sizes.len().max(1) as i128;
+        let tile = i128::from((mean.clamp(1, 1 << 32) as u64).next_power_of_two());
+
+        let mut index = ClaimIndex {
+            claims,
+            tile,
+            tiles: HashMap::new(),
+            large: Vec::new(),
+        };
+        for (i, claim) in index.claims.iter().enumerate() {
+            if claim.is_empty() {
+                continue;
+            }
+            let (tx, ty) = (
+                tile_range(&claim.x_range(), tile),
+                tile_range(&claim.y_range(), tile),
+            );
+            let tiles = (tx.end - tx.start).checked_mul(ty.end - ty.start);
+            if tiles.is_none_or(|tiles| tiles > MAX_TILES) {
+                index.large.push(i);
+                continue;
+            }
+            for ty in ty {
+                for tx in tx.clone() {
+                    index.tiles.entry((tx, ty)).or_default().push(i);
+                }
+            }
+        }
+        index
+    }
+
+    pub fn claims(&self) -> &[Claim<T>] {
+        &self.claims
+    }
+
+    // Claims covering the square inch at (x, y), in input order
+    pub fn at(&self, x: T, y: T) -> Vec<&Claim<T>> {
+        let key = (
+            x.wide().div_euclid(self.tile),
+            y.wide().div_euclid(self.tile),
+        );
+        let mut found = self
+            .tiles
+            .get(&key)
+            .into_iter()
+            .flatten()
+            .chain(&self.large)
+            .cloned()
+            .filter(|i| self.claims[*i].contains(x, y))
+            .collect::<Vec<_>>();
+        found.sort_unstable();
+        found.into_iter().map(|i| &self.claims[i]).collect()
+    }
+
+    // Claims sharing at least one square inch with the rectangle, in input order
+    pub fn within(&self, xs: Range<T>, ys: Range<T>) -> Vec<&Claim<T>> {
+        if xs.is_empty() || ys.is_empty() {
+            return Vec::new();
+        }
+        let mut found = self.large.clone();
+        // Visit whichever is smaller: the tiles under the rectangle, or the tiles there are
+        let (tx, ty) = (tile_range(&xs, self.tile), tile_range(&ys, self.tile));
+        let tiles = (tx.end - tx.start).checked_mul(ty.end - ty.start);
+        if tiles.is_some_and(|tiles| tiles <= self.tiles.len() as i128) {
+            for ty in ty {
+                for tx in tx.clone() {
+                    found.extend(self.tiles.get(&(tx, ty)).into_iter().flatten());
+                }
+            }
+        } else {
+            found.extend(self.tiles.values().flatten());
+        }
+        found.sort_unstable();
+        found.dedup();
+        found
+            .into_iter()
+            .map(|i| &self.claims[i])
+            .filter(|claim| claim.x_range().overlap(&xs) && claim.y_range().overlap(&ys))
+            .collect()
+    }
+
+    // The closest square inch to (x, y) by Manhattan distance that no claim covers, the leftmost
+    // and then the topmost on ties. If it isn't (x, y) itself, moving it towards x would land on a
+    // claim, so it's in column x or just past the left or right edge of some claim. Those columns
+    // are walked up and down from y, jumping over the claims in the way.
+    pub fn nearest_free(&self, x: T, y: T) -> Option<(T, T)> {
+        let mut columns = self
+            .claims
+            .iter()
+            .filter(|claim| !claim.is_empty())
+            .flat_map(|claim| vec![claim.x.wide() - 1, claim.x_range().end.wide()])
+            .chain(Some(x.wide()))
+            .filter_map(T::from_wide)
+            .collect::<Vec<_>>();
+        let dx = |cx: T| (cx.wide() - x.wide()).abs();
+        columns.sort_unstable_by_key(|cx| (dx(*cx), *cx));
+        columns.dedup();
+
+        let mut best: Option<(i128, T, T)> = None;
+        for cx in columns {
+            if best.is_some_and(|(distance, _, _)| dx(cx) > distance) {
+                break;
+            }
+            for cy in [
+                self.free_in_column(cx, y, false),
+                self.free_in_column(cx, y, true),
+            ]
+            .iter()
+            .flatten()
+            {
+                let found = (dx(cx) + (cy.wide() - y.wide()).abs(), cx, *cy);
+                if best.is_none_or(|best| found < best) {
+                    best = Some(found);
+                }
+            }
+        }
+        best.map(|(_, x, y)| (x, y))
+    }
+
+    // The first free square inch in column x going up or down from y. Claims end at or before the
+    // largest coordinate, so going down always finds one.
+    fn free_in_column(&self, x: T, mut y: T, down: bool) -> Option<T> {
+        loop {
+            let covering = self.at(x, y);
+            if covering.is_empty() {
+                return Some(y);
+            }
+            y = if down {
+                covering.iter().map(|claim| claim.y_range().end).max()?
+            } else {
+                T::from_wide(covering.iter().map(|claim| claim.y.wide()).min()? - 1)?
+            };
+        }
+    }
+}
+
+fn tile_range<T: Coord>(range: &Range<T>, tile: i128) -> Range<i128> {
+    range.start.wide().div_euclid(tile)..(range.end.wide() - 1).div_euclid(tile) + 1
+}
+
+#[cfg(test)]
+#[test]
+fn test_claim_index() {
+    let claims = crate::testing::example_claims();
+    let index = ClaimIndex::new(claims);
+    let ids = |claims: Vec<&Claim>| claims.iter().map(|claim| claim.id).collect::<Vec<_>>();
+
+    assert_eq!(ids(index.at(3, 3)), vec![1, 2]);
+    assert_eq!(ids(index.at(6, 6)), vec![3]);
+    assert!(index.at(0, 0).is_empty());
+    assert!(index.at(7, 7).is_empty());
+
+    assert_eq!(ids(index.within(0..2, 0..4)), vec![1]);
+    assert_eq!(ids(index.within(4..6, 4..6)), vec![1, 2, 3]);
+    assert!(index.within(0..1, 0..100).is_empty());
+    assert!(index.within(3..3, 3..4).is_empty());
+    assert_eq!(ids(index.within(0..u16::MAX, 0..u16::MAX)), vec![1, 2, 3]);
+
+    assert_eq!(index.nearest_free(0, 0), Some((0, 0)));
+    assert_eq!(index.nearest_free(4, 4), Some((4, 7)));
+    assert_eq!(index.nearest_free(2, 5), Some((0, 5)));
+
+    // Huge queries look at the claims rather than the tiles under them
+    let wide = ClaimIndex::new(vec![Claim::new(1, 5, 5, 1u64 << 40, 1).unwrap()]);
+    assert_eq!(wide.within(0..u64::MAX, 0..u64::MAX).len(), 1);
+    assert_eq!(wide.within(u64::MAX - 1..u64::MAX, 0..u64::MAX).len(), 0);
+
+    let full = ClaimIndex::new(vec![Claim::new(1, 0, 0, u8::MAX, u8::MAX).unwrap()]);
+    assert_eq!(full.nearest_free(0, 0), Some((0, 255)));
+
+    // Deep inside a huge claim the search jumps straight to its edges
+    let full = ClaimIndex::new(vec![Claim::new(1, 0, 0, u16::MAX, u16::MAX).unwrap()]);
+    assert_eq!(full.nearest_free(30000, 30000), Some((30000, u16::MAX)));
+    let huge = ClaimIndex::new(vec![Claim::new(1, 0, 0, 1u64 << 40, 1 << 40).unwrap()]);
+    assert_eq!(
+        huge.nearest_free(1 << 39, 1 << 39),
+        Some((1 << 39, 1 << 40))
+    );
+    assert_eq!(
+        huge.nearest_free((1 << 40) - 10, 1 << 39),
+        Some((1 << 40, 1 << 39))
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_claim_index_with_one_large_claim() {
+    // One fabric-sized claim among many tiny ones stays out of the tiles
+    let mut next = crate::testing::xorshift(0x3c6e_f372_fe94_f82b_u64);
+    let mut claims = (0..10_000)
+        .map(|id| Claim::new(id + 2, next(30000) as u16, next(30000) as u16, 1, 1).unwrap())
+        .collect::<Vec<_>>();
+    claims.push("#1 @ 0,0: 20000x20000".parse().unwrap());
+    let index = ClaimIndex::new(claims.clone());
+    assert_eq!(index.large, vec![10_000]);
+    assert!(index.tiles.values().map(Vec::len).sum::<usize>() <= 40_000);
+
+    for _ in 0..100 {
+        let (x, y) = (next(30000) as u16, next(30000) as u16);
+        let expected = claims
+            .iter()
+            .filter(|claim| claim.contains(x, y))
+            .collect::<Vec<_>>();
+        assert_eq!(index.at(x, y), expected);
+        let (xs, ys) = (x..x + next(100) as u16, y..y + next(100) as u16);
+        let expected = claims
+            .iter()
+            .filter(|claim| claim.x_range().overlap(&xs) && claim.y_range().overlap(&ys))
+            .collect::<Vec<_>>();
+        assert_eq!(index.within(xs, ys), expected);
+    }
+    let (fx, fy) = index.nearest_free(15000, 10000).unwrap();
+    assert!(index.at(fx, fy).is_empty());
+    assert_eq!(
+        (i32::from(fx) - 15000).abs() + (i32::from(fy) - 10000).abs(),
+        5000
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_claim_index_matches_scan() {
+    let mut next = crate::testing::xorshift(0x6a09_e667_f3bc_c908_u64);
+
+    for _ in 0..100 {
+        let claims = crate::testing::random_claims(&mut next, 30, 40, 0..10);
+        let index = ClaimIndex::new(claims.clone());
+        for _ in 0..20 {
+            let (x, y) = (next(50) as u16, next(50) as u16);
+            let expected = claims
+                .iter()
+                .filter(|claim| claim.contains(x, y))
+                .collect::<Vec<_>>();
+            assert_eq!(index.at(x, y), expected);
+
+            let (xs, ys) = (x..x + next(20) as u16, y..y + next(20) as u16);
+            let expected = claims
+                .iter()
+                .filter(|claim| claim.x_range().overlap(&xs) && claim.y_range().overlap(&ys))
+                .collect::<Vec<_>>();
+            assert_eq!(index.within(xs, ys), expected);
+
+            let (fx, fy) = index.nearest_free(x, y).unwrap();
+            assert!(index.at(fx, fy).is_empty());
+            let distance = |(a, b): (u16, u16)| {
+                (i32::from(a) - i32::from(x)).abs() + (i32::from(b) - i32::from(y)).abs()
+            };
+            let d = distance((fx, fy));
+            for cx in x.saturating_sub(d as u16)..=x + d as u16 {
+                for cy in y.saturating_sub(d as u16)..=y + d as u16 {
+                    if distance((cx, cy)) < d {
+                        assert!(!index.at(cx, cy).is_empty());
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,3 +1,25 @@
+mod area;
+mod coord;
+mod free;
+mod graph;
+mod index;
+mod render;
+mod sweep;
+#[cfg(test)]
+mod testing;
+
+pub use crate::area::{covered_area, overlapping_area, union_area};
+pub use crate::coord::Coord;
+pub use crate::free::FreeSpace;
+pub use crate::graph::{Conflict, ConflictGraph, Conflicted};
+pub use crate::index::ClaimIndex;
+pub use crate::render::{ImageFormat, Picture};
+pub use crate::sweep::intact_claims;
+
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead};
 use std::num::ParseIntError;
 use std::ops::Range;
 use std::str::FromStr;
@@ -6,60 +28,391 @@ trait RangeExt {
     fn overlap(&self, other: &Self) -> bool;
 }
 
-impl RangeExt for Range<u16> {
+impl<T: Coord> RangeExt for Range<T> {
     fn overlap(&self, other: &Self) -> bool {
-        !((self.end - 1) < other.start || self.start > (other.end - 1))
+        // An empty range overlaps nothing, even when it sits inside the other one
+        !self.is_empty() && !other.is_empty() && self.start < other.end && other.start < self.end
     }
 }
 
+// Saturates, so a range too long to index leads to `GridTooLarge` rather than a wrong length
+fn len<T: Coord>(range: &Range<T>) -> usize {
+    usize::try_from((range.end.wide() - range.start.wide()).max(0)).unwrap_or(usize::MAX)
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct Claim {
-    pub id: u16,
-    pub x: u16,
-    pub y: u16,
-    pub width: u16,
-    pub height: u16,
+pub struct GridTooLarge {
+    pub width: usize,
+    pub height: usize,
 }
 
-impl Claim {
-    pub fn x_range(&self) -> Range<u16> {
-        self.x..(self.x + self.width)
+impl fmt::Display for GridTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "a {}x{} grid of the fabric is too large to allocate",
+            self.width, self.height
+        )
+    }
+}
+
+impl Error for GridTooLarge {}
+
+// A dense, row by row grid for the types that keep one value per square inch
+fn grid<V: Clone>(width: usize, height: usize, value: V) -> Result<Vec<V>, GridTooLarge> {
+    let too_large = GridTooLarge { width, height };
+    let size = width.checked_mul(height).ok_or(too_large)?;
+    let mut cells = Vec::new();
+    cells.try_reserve_exact(size).map_err(|_| too_large)?;
+    cells.resize(size, value);
+    Ok(cells)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Claim<T = u16> {
+    pub id: u64,
+    pub x: T,
+    pub y: T,
+    pub width: T,
+    pub height: T,
+}
+
+impl<T: Coord> Claim<T> {
+    // None if the size is negative or the claim reaches past the largest coordinate
+    pub fn new(id: u64, x: T, y: T, width: T, height: T) -> Option<Claim<T>> {
+        if width < T::default() || height < T::default() {
+            return None;
+        }
+        x.checked_add(width)?;
+        y.checked_add(height)?;
+        Some(Claim {
+            id,
+            x,
+            y,
+            width,
+            height,
+        })
+    }
+
+    // A claim built by hand that reaches past the largest coordinate is cut off there, and one with
+    // a negative size is empty
+    pub fn x_range(&self) -> Range<T> {
+        self.x..self.x.saturating_add(self.width)
     }
 
-    pub fn y_range(&self) -> Range<u16> {
-        self.y..(self.y + self.height)
+    pub fn y_range(&self) -> Range<T> {
+        self.y..self.y.saturating_add(self.height)
     }
 
-    pub fn overlap(&self, other: &Claim) -> bool {
+    pub fn overlap(&self, other: &Claim<T>) -> bool {
         self.x_range().overlap(&other.x_range()) && self.y_range().overlap(&other.y_range())
     }
+
+    // The rectangle both claims cover, carrying this claim's ID
+    pub fn intersection(&self, other: &Claim<T>) -> Option<Claim<T>> {
+        if !self.overlap(other) {
+            return None;
+        }
+        let (x, y) = (self.x.max(other.x), self.y.max(other.y));
+        Some(Claim {
+            id: self.id,
+            x,
+            y,
+            width: self.x_range().end.min(other.x_range().end) - x,
+            height: self.y_range().end.min(other.y_range().end) - y,
+        })
+    }
+
+    pub fn contains(&self, x: T, y: T) -> bool {
+        self.x_range().contains(&x) && self.y_range().contains(&y)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.x_range().is_empty() || self.y_range().is_empty()
+    }
 }
 
-impl FromStr for Claim {
-    type Err = ParseIntError;
+// Coverage counts for every square inch inside the bounding box of the claims added so far, stored
+// row by row. The box grows as claims land outside it.
+#[derive(Debug, Clone, Default)]
+pub struct Fabric<T = u16> {
+    xs: Range<T>,
+    ys: Range<T>,
+    cells: Vec<u32>,
+    claims: Vec<Claim<T>>,
+}
 
-    fn from_str(s: &str) -> Result<Claim, ParseIntError> {
-        let mut iter = s.split(' ');
-        let id = iter.next().unwrap().trim_start_matches('#');
-        iter.next();
-        let (x, y) = {
-            let mut iter = iter.next().unwrap().trim_end_matches(':').split(',');
-            (iter.next().unwrap(), iter.next().unwrap())
-        };
-        let (width, height) = {
-            let mut iter = iter.next().unwrap().split('x');
-            (iter.next().unwrap(), iter.next().unwrap())
-        };
-        Ok(Claim {
-            id: id.parse()?,
-            x: x.parse()?,
-            y: y.parse()?,
-            width: width.parse()?,
-            height: height.parse()?,
+impl<T: Coord> Fabric<T> {
+    pub fn new() -> Fabric<T> {
+        Fabric {
+            xs: T::default()..T::default(),
+            ys: T::default()..T::default(),
+            cells: Vec::new(),
+            claims: Vec::new(),
+        }
+    }
+
+    pub fn from_claims(claims: &[Claim<T>]) -> Result<Fabric<T>, GridTooLarge> {
+        let mut fabric = Fabric::new();
+        let claims_box = claims.iter().filter(|claim| !claim.is_empty());
+        if let (Some(x0), Some(x1), Some(y0), Some(y1)) = (
+            claims_box.clone().map(|claim| claim.x_range().start).min(),
+            claims_box.clone().map(|claim| claim.x_range().end).max(),
+            claims_box.clone().map(|claim| claim.y_range().start).min(),
+            claims_box.map(|claim| claim.y_range().end).max(),
+        ) {
+            fabric.resize(x0..x1, y0..y1)?;
+        }
+        for claim in claims {
+            fabric.add(*claim)?;
+        }
+        Ok(fabric)
+    }
+
+    pub fn x_range(&self) -> Range<T> {
+        self.xs.clone()
+    }
+
+    pub fn y_range(&self) -> Range<T> {
+        self.ys.clone()
+    }
+
+    pub fn claims(&self) -> &[Claim<T>] {
+        &self.claims
+    }
+
+    // Fails, leaving the fabric as it was, if the grid would have to grow too large
+    pub fn add(&mut self, claim: Claim<T>) -> Result<(), GridTooLarge> {
+        if !claim.is_empty() {
+            let (xs, ys) = (claim.x_range(), claim.y_range());
+            if xs.start < self.xs.start
+                || xs.end > self.xs.end
+                || ys.start < self.ys.start
+                || ys.end > self.ys.end
+            {
+                if self.cells.is_empty() {
+                    self.resize(xs.clone(), ys.clone())?;
+                } else {
+                    self.resize(
+                        self.xs.start.min(xs.start)..self.xs.end.max(xs.end),
+                        self.ys.start.min(ys.start)..self.ys.end.max(ys.end),
+                    )?;
+                }
+            }
+
+            let first = self.index(xs.start, ys.start).unwrap();
+            let row_len = len(&self.xs);
+            for row in 0..len(&ys) {
+                let start = first + row * row_len;
+                for cell in &mut self.cells[start..start + len(&xs)] {
+                    *cell += 1;
+                }
+            }
+        }
+        self.claims.push(claim);
+        Ok(())
+    }
+
+    fn resize(&mut self, xs: Range<T>, ys: Range<T>) -> Result<(), GridTooLarge> {
+        let mut cells = grid(len(&xs), len(&ys), 0)?;
+        let row_len = len(&self.xs);
+        if row_len > 0 {
+            let shift =
+                len(&(ys.start..self.ys.start)) * len(&xs) + len(&(xs.start..self.xs.start));
+            for (row, old) in self.cells.chunks(row_len).enumerate() {
+                let new = shift + row * len(&xs);
+                cells[new..new + row_len].copy_from_slice(old);
+            }
+        }
+        self.xs = xs;
+        self.ys = ys;
+        self.cells = cells;
+        Ok(())
+    }
+
+    fn index(&self, x: T, y: T) -> Option<usize> {
+        if self.xs.contains(&x) && self.ys.contains(&y) {
+            Some(len(&(self.ys.start..y)) * len(&self.xs) + len(&(self.xs.start..x)))
+        } else {
+            None
+        }
+    }
+
+    // The coverage counts along row y, starting from the left edge of the box
+    fn row(&self, y: T) -> Option<&[u32]> {
+        let start = self.index(self.xs.start, y)?;
+        Some(&self.cells[start..start + len(&self.xs)])
+    }
+
+    // How many claims cover the square inch at (x, y)
+    pub fn coverage(&self, x: T, y: T) -> u32 {
+        self.index(x, y).map_or(0, |i| self.cells[i])
+    }
+
+    // Square inches covered by two or more claims
+    pub fn overlapping(&self) -> usize {
+        self.cells.iter().filter(|n| **n >= 2).count()
+    }
+
+    pub fn claims_at(&self, x: T, y: T) -> Vec<u64> {
+        self.claims
+            .iter()
+            .filter(|claim| claim.contains(x, y))
+            .map(|claim| claim.id)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Id,
+    X,
+    Y,
+    Width,
+    Height,
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Field::Id => "id",
+            Field::X => "x",
+            Field::Y => "y",
+            Field::Width => "width",
+            Field::Height => "height",
         })
     }
 }
 
+// Offsets are in bytes from the start of the line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClaimParseError {
+    // The field, or the separator in front of it, isn't there
+    Missing {
+        field: Field,
+        offset: usize,
+    },
+    Invalid {
+        field: Field,
+        offset: usize,
+        err: ParseIntError,
+    },
+    // Negative, or reaching past the largest coordinate
+    OutOfRange {
+        field: Field,
+        offset: usize,
+    },
+    Trailing {
+        offset: usize,
+    },
+}
+
+impl fmt::Display for ClaimParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClaimParseError::Missing { field, offset } => {
+                write!(f, "missing {} at byte {}", field, offset)
+            }
+            ClaimParseError::Invalid { field, offset, err } => {
+                write!(f, "invalid {} at byte {}: {}", field, offset, err)
+            }
+            ClaimParseError::OutOfRange { field, offset } => {
+                write!(f, "{} at byte {} is out of range", field, offset)
+            }
+            ClaimParseError::Trailing { offset } => {
+                write!(f, "unexpected text at byte {}", offset)
+            }
+        }
+    }
+}
+
+impl Error for ClaimParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ClaimParseError::Invalid { err, .. } => Some(err),
+            _ => None,
+        }
+    }
+}
+
+fn parse_field<V: FromStr<Err = ParseIntError>>(
+    s: &str,
+    offset: &mut usize,
+    separator: &str,
+    field: Field,
+) -> Result<(V, usize), ClaimParseError> {
+    if !s[*offset..].starts_with(separator) {
+        return Err(ClaimParseError::Missing {
+            field,
+            offset: *offset,
+        });
+    }
+    let start = *offset + separator.len();
+    let len = s[start..]
+        .find(|c: char| c.is_whitespace() || "#@,:x".contains(c))
+        .unwrap_or(s.len() - start);
+    if len == 0 {
+        return Err(ClaimParseError::Missing {
+            field,
+            offset: start,
+        });
+    }
+    let value = s[start..start + len]
+        .parse()
+        .map_err(|err| ClaimParseError::Invalid {
+            field,
+            offset: start,
+            err,
+        })?;
+    *offset = start + len;
+    Ok((value, start))
+}
+
+impl<T: Coord> FromStr for Claim<T> {
+    type Err = ClaimParseError;
+
+    fn from_str(s: &str) -> Result<Claim<T>, ClaimParseError> {
+        let mut offset = 0;
+        let (id, _) = parse_field(s, &mut offset, "#", Field::Id)?;
+        let (x, _): (T, _) = parse_field(s, &mut offset, " @ ", Field::X)?;
+        let (y, _) = parse_field(s, &mut offset, ",", Field::Y)?;
+        let (width, width_at): (T, _) = parse_field(s, &mut offset, ": ", Field::Width)?;
+        let (height, height_at) = parse_field(s, &mut offset, "x", Field::Height)?;
+        if offset < s.len() {
+            return Err(ClaimParseError::Trailing { offset });
+        }
+        Claim::new(id, x, y, width, height).ok_or_else(|| {
+            if width < T::default() || x.checked_add(width).is_none() {
+                ClaimParseError::OutOfRange {
+                    field: Field::Width,
+                    offset: width_at,
+                }
+            } else {
+                ClaimParseError::OutOfRange {
+                    field: Field::Height,
+                    offset: height_at,
+                }
+            }
+        })
+    }
+}
+
+// Reads a claim from every line, handing lines that don't parse to `bad_line` along with their line
+// number
+pub fn read_claims<T: Coord, R: BufRead>(
+    reader: R,
+    mut bad_line: impl FnMut(usize, ClaimParseError),
+) -> io::Result<Vec<Claim<T>>> {
+    let mut claims = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        match line?.parse() {
+            Ok(claim) => claims.push(claim),
+            Err(err) => bad_line(i + 1, err),
+        }
+    }
+    Ok(claims)
+}
+
 #[cfg(test)]
 #[test]
 fn test_claim_from_str() {
@@ -75,32 +428,236 @@ fn test_claim_from_str() {
     );
 }
 
+#[cfg(test)]
+#[test]
+fn test_claim_parse_error() {
+    let err = |s: &str| s.parse::<Claim>().unwrap_err();
+    assert_eq!(
+        err("#1 @ 1,3 4x4"),
+        ClaimParseError::Missing {
+            field: Field::Width,
+            offset: 8
+        }
+    );
+    assert_eq!(
+        err(""),
+        ClaimParseError::Missing {
+            field: Field::Id,
+            offset: 0
+        }
+    );
+    assert_eq!(
+        err("#1 @ ,3: 4x4"),
+        ClaimParseError::Missing {
+            field: Field::X,
+            offset: 5
+        }
+    );
+    assert_eq!(
+        err("#1 @ 1,3: 4x"),
+        ClaimParseError::Missing {
+            field: Field::Height,
+            offset: 12
+        }
+    );
+    match err("#1 @ 1,3: 4ax4") {
+        ClaimParseError::Invalid { field, offset, .. } => {
+            assert_eq!((field, offset), (Field::Width, 10))
+        }
+        err => panic!("{:?}", err),
+    }
+    match err("#1 @ 70000,3: 4x4") {
+        ClaimParseError::Invalid { field, offset, .. } => {
+            assert_eq!((field, offset), (Field::X, 5))
+        }
+        err => panic!("{:?}", err),
+    }
+    assert_eq!(
+        err("#1 @ 1,3: 4x4 extra"),
+        ClaimParseError::Trailing { offset: 13 }
+    );
+    assert_eq!(err("#1 @ 1,3 4x4").to_string(), "missing width at byte 8");
+
+    let mut bad = Vec::new();
+    let claims = read_claims::<u16, _>(
+        "#1 @ 1,3: 4x4\n#2 @ 3,1 4x4\n#3 @ 5,5: 2x2\n".as_bytes(),
+        |line, err| bad.push((line, err)),
+    )
+    .unwrap();
+    assert_eq!(claims.iter().map(|c| c.id).collect::<Vec<_>>(), vec![1, 3]);
+    assert_eq!(
+        bad,
+        vec![(
+            2,
+            ClaimParseError::Missing {
+                field: Field::Width,
+                offset: 8
+            }
+        )]
+    );
+}
+
 #[cfg(test)]
 #[test]
 fn test_range_overlap() {
-    assert_eq!((3..8).overlap(&(1..3)), false);
-    assert_eq!((3..8).overlap(&(2..4)), true);
-    assert_eq!((3..8).overlap(&(4..7)), true);
-    assert_eq!((3..8).overlap(&(7..9)), true);
-    assert_eq!((3..8).overlap(&(8..10)), false);
-    assert_eq!((1..3).overlap(&(3..8)), false);
-    assert_eq!((2..4).overlap(&(3..8)), true);
-    assert_eq!((4..7).overlap(&(3..8)), true);
-    assert_eq!((7..9).overlap(&(3..8)), true);
-    assert_eq!((8..10).overlap(&(3..8)), false);
+    assert!(!(3..8).overlap(&(1..3)));
+    assert!((3..8).overlap(&(2..4)));
+    assert!((3..8).overlap(&(4..7)));
+    assert!((3..8).overlap(&(7..9)));
+    assert!(!(3..8).overlap(&(8..10)));
+    assert!(!(1..3).overlap(&(3..8)));
+    assert!((2..4).overlap(&(3..8)));
+    assert!((4..7).overlap(&(3..8)));
+    assert!((7..9).overlap(&(3..8)));
+    assert!(!(8..10).overlap(&(3..8)));
 }
 
 #[cfg(test)]
 #[test]
 fn test_claim_overlap() {
-    let a = Claim::from_str("#1 @ 1,3: 4x4").unwrap();
-    let b = Claim::from_str("#2 @ 3,1: 4x4").unwrap();
-    let c = Claim::from_str("#3 @ 5,5: 2x2").unwrap();
-
-    assert_eq!(a.overlap(&b), true);
-    assert_eq!(b.overlap(&a), true);
-    assert_eq!(a.overlap(&c), false);
-    assert_eq!(c.overlap(&a), false);
-    assert_eq!(b.overlap(&c), false);
-    assert_eq!(c.overlap(&b), false);
+    let a: Claim = Claim::from_str("#1 @ 1,3: 4x4").unwrap();
+    let b: Claim = Claim::from_str("#2 @ 3,1: 4x4").unwrap();
+    let c: Claim = Claim::from_str("#3 @ 5,5: 2x2").unwrap();
+
+    assert!(a.overlap(&b));
+    assert!(b.overlap(&a));
+    assert!(!a.overlap(&c));
+    assert!(!c.overlap(&a));
+    assert!(!b.overlap(&c));
+    assert!(!c.overlap(&b));
+}
+
+#[cfg(test)]
+#[test]
+fn test_claim_bounds() {
+    // Claims reaching right up to the largest coordinate are fine, past it aren't
+    let edge = "#100000 @ 65530,0: 5x1".parse::<Claim>().unwrap();
+    assert_eq!(edge.id, 100_000);
+    assert_eq!(edge.x_range(), 65530..65535);
+    assert_eq!(
+        "#1 @ 65530,0: 6x1".parse::<Claim>(),
+        Err(ClaimParseError::OutOfRange {
+            field: Field::Width,
+            offset: 14
+        })
+    );
+    assert_eq!(
+        "#1 @ 0,65535: 1x1".parse::<Claim>(),
+        Err(ClaimParseError::OutOfRange {
+            field: Field::Height,
+            offset: 16
+        })
+    );
+    assert_eq!(
+        "#1 @ 0,0: -1x1".parse::<Claim<i32>>(),
+        Err(ClaimParseError::OutOfRange {
+            field: Field::Width,
+            offset: 10
+        })
+    );
+    assert!("#1 @ 65530,0: 7x1".parse::<Claim<u32>>().is_ok());
+    assert_eq!(Claim::new(1, u64::MAX - 1, 0, 2, 1), None);
+
+    // Hand-built claims past the edge are cut off, and empty claims overlap nothing
+    let huge: Claim = Claim {
+        id: 1,
+        x: 65000,
+        y: 0,
+        width: 1000,
+        height: 1,
+    };
+    assert_eq!(huge.x_range(), 65000..65535);
+    let empty: Claim = Claim {
+        id: 2,
+        x: 65100,
+        y: 0,
+        width: 0,
+        height: 1,
+    };
+    assert!(empty.is_empty());
+    assert!(!empty.overlap(&huge));
+    assert!(!huge.overlap(&empty));
+    assert!(!empty.overlap(&empty));
+    assert_eq!(huge.intersection(&empty), None);
+    assert!(!(5..5).overlap(&(0..10)));
+
+    let negative = Claim::new(1, -10, -10, 5, 5).unwrap();
+    assert!(negative.overlap(&Claim::new(2, -6, -6, 1, 1).unwrap()));
+    assert!(!negative.overlap(&Claim::new(2, -5, -5, 1, 1).unwrap()));
+}
+
+#[cfg(test)]
+#[test]
+fn test_intersection() {
+    let claim = |s: &str| s.parse::<Claim>().unwrap();
+    assert_eq!(
+        claim("#1 @ 1,3: 4x4").intersection(&claim("#2 @ 3,1: 4x4")),
+        Some(claim("#1 @ 3,3: 2x2"))
+    );
+    assert_eq!(
+        claim("#2 @ 3,1: 4x4").intersection(&claim("#1 @ 1,3: 4x4")),
+        Some(claim("#2 @ 3,3: 2x2"))
+    );
+    assert_eq!(
+        claim("#1 @ 0,0: 10x10").intersection(&claim("#2 @ 2,3: 1x1")),
+        Some(claim("#1 @ 2,3: 1x1"))
+    );
+    assert_eq!(
+        claim("#1 @ 0,0: 2x2").intersection(&claim("#2 @ 2,0: 2x2")),
+        None
+    );
+    assert_eq!(
+        claim("#1 @ 1,3: 4x4").intersection(&claim("#3 @ 5,5: 2x2")),
+        None
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_fabric() {
+    let claims = crate::testing::example_claims();
+    let fabric = Fabric::from_claims(&claims).unwrap();
+    assert_eq!(fabric.x_range(), 1..7);
+    assert_eq!(fabric.y_range(), 1..7);
+    assert_eq!(fabric.overlapping(), 4);
+    assert_eq!(fabric.coverage(0, 0), 0);
+    assert_eq!(fabric.coverage(1, 3), 1);
+    assert_eq!(fabric.coverage(4, 4), 2);
+    assert_eq!(fabric.coverage(100, 100), 0);
+    assert_eq!(fabric.claims_at(3, 3), vec![1, 2]);
+    assert_eq!(fabric.claims_at(6, 6), vec![3]);
+    assert!(fabric.claims_at(0, 0).is_empty());
+
+    // Adding claims one at a time grows the fabric as needed
+    let mut grown = Fabric::new();
+    for claim in claims.iter().rev() {
+        grown.add(*claim).unwrap();
+    }
+    for x in 0..8 {
+        for y in 0..8 {
+            assert_eq!(grown.coverage(x, y), fabric.coverage(x, y));
+        }
+    }
+    grown.add("#4 @ 0,0: 2x2".parse().unwrap()).unwrap();
+    assert_eq!(grown.x_range(), 0..7);
+    assert_eq!(grown.coverage(1, 1), 1);
+    assert_eq!(grown.coverage(4, 4), 2);
+    assert_eq!(grown.overlapping(), 4);
+
+    // Grids too big to index or allocate are refused
+    let huge = Claim::<u64>::new(1, 0, 0, 1 << 32, 1 << 32).unwrap();
+    assert!(Fabric::from_claims(&[huge]).is_err());
+    let mut fabric = Fabric::new();
+    fabric
+        .add(Claim::<u64>::new(1, 0, 0, 2, 2).unwrap())
+        .unwrap();
+    assert_eq!(
+        fabric.add(Claim::new(2, u64::MAX - 1, u64::MAX - 1, 1, 1).unwrap()),
+        Err(GridTooLarge {
+            width: usize::MAX,
+            height: usize::MAX
+        })
+    );
+    assert_eq!(fabric.claims().len(), 1);
+    assert_eq!(fabric.coverage(1, 1), 1);
 }
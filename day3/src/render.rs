@@ -0,0 +1,255 @@
+use crate::{intact_claims, len, Claim, Coord, Fabric, GridTooLarge};
+use failure::Fallible;
+use std::io::{self, Write};
+use std::ops::Range;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    // The diagram from the puzzle: claim IDs, X where claims overlap
+    Ascii,
+    // Greyscale heatmap of how many claims cover each square inch
+    Pgm,
+    // Intact claims in green, other claims in blue, overlaps in red
+    Ppm,
+}
+
+impl FromStr for ImageFormat {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Fallible<ImageFormat> {
+        match s {
+            "ascii" => Ok(ImageFormat::Ascii),
+            "pgm" => Ok(ImageFormat::Pgm),
+            "ppm" => Ok(ImageFormat::Ppm),
+            s => Err(failure::format_err!("unknown image format {:?}", s)),
+        }
+    }
+}
+
+// The fabric around the claims, one pixel per square inch. The picture is cropped to the claims'
+// bounding box plus a one inch margin rather than starting at (0, 0); the puzzle's diagram only
+// looks the same because its claims start at 1,1.
+#[derive(Debug, Clone)]
+pub struct Picture<T = u16> {
+    fabric: Fabric<T>,
+    // Whether each of the fabric's claims is intact, in the same order
+    intact: Vec<bool>,
+    xs: Range<i128>,
+    ys: Range<i128>,
+}
+
+impl<T: Coord> Picture<T> {
+    pub fn new(claims: &[Claim<T>]) -> Result<Picture<T>, GridTooLarge> {
+        let fabric = Fabric::from_claims(claims)?;
+        let mut intact = intact_claims(claims).into_iter().peekable();
+        let intact = claims
+            .iter()
+            .map(|claim| intact.next_if_eq(claim).is_some())
+            .collect();
+        let margin = |range: Range<T>| {
+            if range.is_empty() {
+                0..0
+            } else {
+                (range.start.wide() - 1).max(T::MIN.wide())..range.end.wide() + 1
+            }
+        };
+        Ok(Picture {
+            xs: margin(fabric.x_range()),
+            ys: margin(fabric.y_range()),
+            fabric,
+            intact,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        (self.xs.end - self.xs.start) as usize
+    }
+
+    pub fn height(&self) -> usize {
+        (self.ys.end - self.ys.start) as usize
+    }
+
+    // The coverage of each pixel in a row and, where a single claim covers it, that claim's index.
+    // Owners are found by painting the claims crossing the row, so nothing per pixel is kept.
+    fn row(&self, y: i128) -> Vec<(u32, Option<usize>)> {
+        let mut row = vec![(0, None); self.width()];
+        let y = match T::from_wide(y) {
+            Some(y) => y,
+            None => return row,
+        };
+        if let Some(cells) = self.fabric.row(y) {
+            let offset = (self.fabric.x_range().start.wide() - self.xs.start) as usize;
+            for (pixel, n) in row[offset..].iter_mut().zip(cells) {
+                pixel.0 = *n;
+            }
+            for (i, claim) in self.fabric.claims().iter().enumerate() {
+                // Empty claims aren't part of the fabric's box, so they could lie outside the row
+                if claim.is_empty() || !claim.y_range().contains(&y) {
+                    continue;
+                }
+                let start = (claim.x.wide() - self.xs.start) as usize;
+                for pixel in &mut row[start..start + len(&claim.x_range())] {
+                    if pixel.0 == 1 {
+                        pixel.1 = Some(i);
+                    }
+                }
+            }
+        }
+        row
+    }
+
+    pub fn write<W: Write>(&self, writer: W, format: ImageFormat) -> io::Result<()> {
+        match format {
+            ImageFormat::Ascii => self.write_ascii(writer),
+            ImageFormat::Pgm => self.write_pgm(writer),
+            ImageFormat::Ppm => self.write_ppm(writer),
+        }
+    }
+
+    // IDs past 9 show their last digit
+    pub fn write_ascii<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let claims = self.fabric.claims();
+        for y in self.ys.clone() {
+            let line = self
+                .row(y)
+                .into_iter()
+                .map(|pixel| match pixel {
+                    (_, Some(i)) => char::from(b'0' + (claims[i].id % 10) as u8),
+                    (0, None) => '.',
+                    _ => 'X',
+                })
+                .collect::<String>();
+            writeln!(writer, "{}", line)?;
+        }
+        Ok(())
+    }
+
+    pub fn write_pgm<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let max = self.fabric.cells.iter().cloned().max().unwrap_or(0).max(1);
+        writeln!(writer, "P5\n{} {}\n255", self.width(), self.height())?;
+        for y in self.ys.clone() {
+            let pixels = self
+                .row(y)
+                .into_iter()
+                .map(|(n, _)| (u64::from(n) * 255 / u64::from(max)) as u8)
+                .collect::<Vec<_>>();
+            writer.write_all(&pixels)?;
+        }
+        Ok(())
+    }
+
+    pub fn write_ppm<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "P6\n{} {}\n255", self.width(), self.height())?;
+        for y in self.ys.clone() {
+            let pixels = self
+                .row(y)
+                .into_iter()
+                .flat_map(|pixel| match pixel {
+                    (_, Some(i)) if self.intact[i] => [0, 160, 0],
+                    (_, Some(_)) => [96, 128, 224],
+                    (0, None) => [255, 255, 255],
+                    _ => [224, 32, 32],
+                })
+                .collect::<Vec<_>>();
+            writer.write_all(&pixels)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_ascii() {
+    let claims = crate::testing::example_claims();
+    let mut ascii = Vec::new();
+    Picture::new(&claims)
+        .unwrap()
+        .write_ascii(&mut ascii)
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(ascii).unwrap(),
+        "........\n\
+         ...2222.\n\
+         ...2222.\n\
+         .11XX22.\n\
+         .11XX22.\n\
+         .111133.\n\
+         .111133.\n\
+         ........\n"
+    );
+
+    let mut ascii = Vec::new();
+    Picture::<u16>::new(&[])
+        .unwrap()
+        .write_ascii(&mut ascii)
+        .unwrap();
+    assert!(ascii.is_empty());
+}
+
+#[cfg(test)]
+#[test]
+fn test_images() {
+    let claims = ["#1 @ 0,0: 2x1", "#2 @ 1,0: 1x1", "#3 @ 3,0: 1x1"]
+        .iter()
+        .map(|s| s.parse().unwrap())
+        .collect::<Vec<Claim>>();
+    let picture = Picture::new(&claims).unwrap();
+    assert_eq!((picture.width(), picture.height()), (5, 2));
+
+    let mut pgm = Vec::new();
+    picture.write(&mut pgm, ImageFormat::Pgm).unwrap();
+    let mut expected = b"P5\n5 2\n255\n".to_vec();
+    expected.extend_from_slice(&[127, 255, 0, 127, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(pgm, expected);
+
+    let mut ppm = Vec::new();
+    picture.write(&mut ppm, ImageFormat::Ppm).unwrap();
+    let mut expected = b"P6\n5 2\n255\n".to_vec();
+    for pixel in &[
+        [96, 128, 224],
+        [224, 32, 32],
+        [255, 255, 255],
+        [0, 160, 0],
+        [255, 255, 255],
+    ] {
+        expected.extend_from_slice(pixel);
+    }
+    for _ in 0..5 {
+        expected.extend_from_slice(&[255, 255, 255]);
+    }
+    assert_eq!(ppm, expected);
+
+    // Empty claims outside the box draw nothing
+    for empty in &["#2 @ 50,10: 0x2", "#2 @ 0,10: 0x2"] {
+        let claims = ["#1 @ 10,10: 2x2", empty]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect::<Vec<Claim>>();
+        let mut ascii = Vec::new();
+        Picture::new(&claims)
+            .unwrap()
+            .write_ascii(&mut ascii)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(ascii).unwrap(),
+            "....\n.11.\n.11.\n....\n"
+        );
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_margin_at_coordinate_limits() {
+    // No margin before the smallest coordinate; the margin past the largest one is blank
+    let claims = [
+        Claim::<u8>::new(1, 0, 254, 1, 1).unwrap(),
+        Claim::new(2, 1, 254, 1, 1).unwrap(),
+    ];
+    let mut ascii = Vec::new();
+    Picture::new(&claims)
+        .unwrap()
+        .write_ascii(&mut ascii)
+        .unwrap();
+    assert_eq!(String::from_utf8(ascii).unwrap(), "...\n12.\n...\n");
+}
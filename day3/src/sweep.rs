@@ -0,0 +1,219 @@
+use crate::{Claim, Coord};
+
+// Range add and range max over a fixed number of slots; tracks how many claims cover each slot.
+struct CoverTree {
+    add: Vec<i32>,
+    max: Vec<i32>,
+    len: usize,
+}
+
+impl CoverTree {
+    fn new(len: usize) -> CoverTree {
+        CoverTree {
+            add: vec![0; 4 * len],
+            max: vec![0; 4 * len],
+            len,
+        }
+    }
+
+    fn update(&mut self, start: usize, end: usize, value: i32) {
+        self.update_node(1, 0, self.len, start, end, value);
+    }
+
+    fn update_node(
+        &mut self,
+        node: usize,
+        lo: usize,
+        hi: usize,
+        start: usize,
+        end: usize,
+        value: i32,
+    ) {
+        if end <= lo || hi <= start {
+            return;
+        }
+        if start <= lo && hi <= end {
+            self.add[node] += value;
+            self.max[node] += value;
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        self.update_node(2 * node, lo, mid, start, end, value);
+        self.update_node(2 * node + 1, mid, hi, start, end, value);
+        self.max[node] = self.add[node] + self.max[2 * node].max(self.max[2 * node + 1]);
+    }
+
+    fn query(&self, start: usize, end: usize) -> i32 {
+        self.query_node(1, 0, self.len, start, end)
+    }
+
+    fn query_node(&self, node: usize, lo: usize, hi: usize, start: usize, end: usize) -> i32 {
+        if end <= lo || hi <= start {
+            return i32::MIN;
+        }
+        if start <= lo && hi <= end {
+            return self.max[node];
+        }
+        let mid = (lo + hi) / 2;
+        let below = self
+            .query_node(2 * node, lo, mid, start, end)
+            .max(self.query_node(2 * node + 1, mid, hi, start, end));
+        self.add[node] + below
+    }
+}
+
+// Range "raise to at least" and range max; remembers the latest claim placed over each slot.
+struct StampTree {
+    stamp: Vec<usize>,
+    max: Vec<usize>,
+    len: usize,
+}
+
+impl StampTree {
+    fn new(len: usize) -> StampTree {
+        StampTree {
+            stamp: vec![0; 4 * len],
+            max: vec![0; 4 * len],
+            len,
+        }
+    }
+
+    fn update(&mut self, start: usize, end: usize, value: usize) {
+        self.update_node(1, 0, self.len, start, end, value);
+    }
+
+    fn update_node(
+        &mut self,
+        node: usize,
+        lo: usize,
+        hi: usize,
+        start: usize,
+        end: usize,
+        value: usize,
+    ) {
+        if end <= lo || hi <= start {
+            return;
+        }
+        self.max[node] = self.max[node].max(value);
+        if start <= lo && hi <= end {
+            self.stamp[node] = self.stamp[node].max(value);
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        self.update_node(2 * node, lo, mid, start, end, value);
+        self.update_node(2 * node + 1, mid, hi, start, end, value);
+    }
+
+    fn query(&self, start: usize, end: usize) -> usize {
+        self.query_node(1, 0, self.len, start, end)
+    }
+
+    fn query_node(&self, node: usize, lo: usize, hi: usize, start: usize, end: usize) -> usize {
+        if end <= lo || hi <= start {
+            return 0;
+        }
+        if start <= lo && hi <= end {
+            return self.max[node];
+        }
+        let mid = (lo + hi) / 2;
+        self.stamp[node]
+            .max(self.query_node(2 * node, lo, mid, start, end))
+            .max(self.query_node(2 * node + 1, mid, hi, start, end))
+    }
+}
+
+// Sweeps across x, keeping the y-extents of the claims that are currently open in two segment trees
+// over the compressed y coordinates. A claim overlaps something if, when it opens, another open
+// claim already covers part of its y-extent, or if another claim opens over its y-extent before it
+// closes. Both checks are a range query, so the whole sweep takes O(n log n).
+pub fn intact_claims<T: Coord>(claims: &[Claim<T>]) -> Vec<Claim<T>> {
+    let mut ys = claims
+        .iter()
+        .flat_map(|claim| vec![claim.y_range().start, claim.y_range().end])
+        .collect::<Vec<_>>();
+    ys.sort_unstable();
+    ys.dedup();
+    let slot = |y| ys.binary_search(&y).unwrap();
+
+    // (x, opens, claim); claims close before others open at the same x
+    let mut events = Vec::new();
+    for (i, claim) in claims.iter().enumerate() {
+        if !claim.is_empty() {
+            events.push((claim.x_range().start, true, i));
+            events.push((claim.x_range().end, false, i));
+        }
+    }
+    events.sort_unstable();
+
+    let mut cover = CoverTree::new(ys.len());
+    let mut stamps = StampTree::new(ys.len());
+    let mut opened_at = vec![0; claims.len()];
+    let mut overlapped = vec![false; claims.len()];
+    for (stamp, (_, opens, i)) in events.into_iter().enumerate() {
+        let range = claims[i].y_range();
+        let (start, end) = (slot(range.start), slot(range.end));
+        if opens {
+            if cover.query(start, end) > 0 {
+                overlapped[i] = true;
+            }
+            cover.update(start, end, 1);
+            // Stamps start at 1 so an untouched slot never looks newer than a claim
+            opened_at[i] = stamp + 1;
+            stamps.update(start, end, stamp + 1);
+        } else {
+            cover.update(start, end, -1);
+            if stamps.query(start, end) > opened_at[i] {
+                overlapped[i] = true;
+            }
+        }
+    }
+
+    claims
+        .iter()
+        .zip(overlapped)
+        .filter(|(_, overlapped)| !overlapped)
+        .map(|(claim, _)| *claim)
+        .collect()
+}
+
+#[cfg(test)]
+#[test]
+fn test_intact_claims() {
+    let claims = crate::testing::example_claims();
+    assert_eq!(intact_claims(&claims), vec![claims[2]]);
+
+    // Touching edges don't overlap, and a claim inside another does
+    let claims = [
+        "#1 @ 0,0: 2x2",
+        "#2 @ 2,0: 2x2",
+        "#3 @ 0,2: 4x1",
+        "#4 @ 5,5: 5x5",
+        "#5 @ 6,6: 1x1",
+    ]
+    .iter()
+    .map(|s| s.parse().unwrap())
+    .collect::<Vec<Claim>>();
+    assert_eq!(intact_claims(&claims), claims[..3].to_vec());
+}
+
+#[cfg(test)]
+#[test]
+fn test_intact_claims_matches_brute_force() {
+    let mut next = crate::testing::xorshift(0xda94_2042_e4dd_58b5_u64);
+
+    for _ in 0..500 {
+        let claims = crate::testing::random_claims(&mut next, 30, 40, 1..11);
+        let expected = claims
+            .iter()
+            .enumerate()
+            .filter(|(i, a)| {
+                claims
+                    .iter()
+                    .enumerate()
+                    .all(|(j, b)| *i == j || !a.overlap(b))
+            })
+            .map(|(_, a)| *a)
+            .collect::<Vec<_>>();
+        assert_eq!(intact_claims(&claims), expected, "{:?}", claims);
+    }
+}
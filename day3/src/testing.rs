@@ -0,0 +1,39 @@
+use crate::Claim;
+use std::ops::Range;
+
+// xorshift, so the randomized tests don't need rand. The closure returns numbers below its bound.
+pub fn xorshift(seed: u64) -> impl FnMut(u64) -> u64 {
+    let mut state = seed;
+    move |bound| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state % bound
+    }
+}
+
+// The claims from the puzzle's example
+pub fn example_claims() -> Vec<Claim> {
+    ["#1 @ 1,3: 4x4", "#2 @ 3,1: 4x4", "#3 @ 5,5: 2x2"]
+        .iter()
+        .map(|s| s.parse().unwrap())
+        .collect()
+}
+
+// Fewer than `count` claims with corners below `span` and sides in `sizes`
+pub fn random_claims(
+    next: &mut impl FnMut(u64) -> u64,
+    count: u64,
+    span: u64,
+    sizes: Range<u64>,
+) -> Vec<Claim> {
+    (0..next(count))
+        .map(|id| Claim {
+            id,
+            x: next(span) as u16,
+            y: next(span) as u16,
+            width: (sizes.start + next(sizes.end - sizes.start)) as u16,
+            height: (sizes.start + next(sizes.end - sizes.start)) as u16,
+        })
+        .collect()
+}
@@ -1,19 +1,72 @@
+mod stats;
+
+pub use crate::stats::{
+    Answer, GuardStats, LongestNap, MostAsleep, MostFrequentMinute, MostNights, Strategy, Window,
+};
+
 use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Timelike};
 use failure::Fallible;
 use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::error::Error;
 use std::fmt;
 use std::io::{self, BufRead, BufReader};
-use std::num::ParseIntError;
-use std::ops::{Add, Sub};
+use std::ops::{Add, Range, Sub};
 use std::str::FromStr;
 
 pub fn read_events() -> impl Iterator<Item = Fallible<Event>> {
-    BufReader::new(io::stdin()).lines().map(|line| {
-        line.map_err(failure::Error::from)
-            .and_then(|line| line.parse().map_err(failure::Error::from))
+    read_events_from(BufReader::new(io::stdin()))
+}
+
+pub fn read_events_from<R: BufRead>(reader: R) -> impl Iterator<Item = Fallible<Event>> {
+    reader.lines().enumerate().map(|(i, line)| {
+        let event = line?.parse::<Event>().map_err(|err| err.on_line(i + 1))?;
+        Ok(event)
     })
 }
 
+// The line is only known when reading a log; parsing a single event leaves it out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventParseError {
+    BadTimestamp { line: Option<usize>, text: String },
+    UnknownAction { line: Option<usize>, text: String },
+    MissingGuardId { line: Option<usize>, text: String },
+}
+
+impl EventParseError {
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            EventParseError::BadTimestamp { line, .. }
+            | EventParseError::UnknownAction { line, .. }
+            | EventParseError::MissingGuardId { line, .. } => *line,
+        }
+    }
+
+    fn on_line(mut self, n: usize) -> EventParseError {
+        match &mut self {
+            EventParseError::BadTimestamp { line, .. }
+            | EventParseError::UnknownAction { line, .. }
+            | EventParseError::MissingGuardId { line, .. } => *line = Some(n),
+        }
+        self
+    }
+}
+
+impl fmt::Display for EventParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(line) = self.line() {
+            write!(f, "line {}: ", line)?;
+        }
+        match self {
+            EventParseError::BadTimestamp { text, .. } => write!(f, "bad timestamp {:?}", text),
+            EventParseError::UnknownAction { text, .. } => write!(f, "unknown action {:?}", text),
+            EventParseError::MissingGuardId { text, .. } => write!(f, "no guard ID in {:?}", text),
+        }
+    }
+}
+
+impl Error for EventParseError {}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Time {
     pub year: i32,
@@ -30,17 +83,32 @@ impl Time {
 }
 
 impl FromStr for Time {
-    type Err = ParseIntError;
-
-    fn from_str(s: &str) -> Result<Time, ParseIntError> {
-        let mut iter = s.split(|c: char| !c.is_numeric());
-        Ok(Time {
-            year: iter.next().unwrap().parse()?,
-            month: iter.next().unwrap().parse()?,
-            day: iter.next().unwrap().parse()?,
-            hour: iter.next().unwrap().parse()?,
-            minute: iter.next().unwrap().parse()?,
-        })
+    type Err = EventParseError;
+
+    fn from_str(s: &str) -> Result<Time, EventParseError> {
+        let bad = || EventParseError::BadTimestamp {
+            line: None,
+            text: s.to_string(),
+        };
+        let fields = s
+            .split(|c: char| !c.is_numeric())
+            .map(|field| field.parse().map_err(|_| bad()))
+            .collect::<Result<Vec<u32>, _>>()?;
+        let time = match fields[..] {
+            [year, month, day, hour, minute] => Time {
+                year: i32::try_from(year).map_err(|_| bad())?,
+                month,
+                day,
+                hour,
+                minute,
+            },
+            _ => return Err(bad()),
+        };
+        // Converting to a NaiveDateTime panics on a time that doesn't exist
+        NaiveDate::from_ymd_opt(time.year, time.month, time.day)
+            .and_then(|date| date.and_hms_opt(time.hour, time.minute, 0))
+            .ok_or_else(bad)?;
+        Ok(time)
     }
 }
 
@@ -105,7 +173,7 @@ impl Eq for Event {}
 
 impl PartialOrd for Event {
     fn partial_cmp(&self, other: &Event) -> Option<Ordering> {
-        Some(self.cmp(&other))
+        Some(self.cmp(other))
     }
 }
 
@@ -116,14 +184,19 @@ impl Ord for Event {
 }
 
 impl FromStr for Event {
-    type Err = ParseIntError;
+    type Err = EventParseError;
 
-    fn from_str(s: &str) -> Result<Event, ParseIntError> {
-        let mut iter = s.split("] ");
-        Ok(Event {
-            time: iter.next().unwrap().trim_start_matches('[').parse()?,
-            what: iter.next().unwrap().parse()?,
-        })
+    fn from_str(s: &str) -> Result<Event, EventParseError> {
+        match s.strip_prefix('[').and_then(|s| s.split_once("] ")) {
+            Some((time, what)) => Ok(Event {
+                time: time.parse()?,
+                what: what.parse()?,
+            }),
+            None => Err(EventParseError::BadTimestamp {
+                line: None,
+                text: s.to_string(),
+            }),
+        }
     }
 }
 
@@ -141,21 +214,27 @@ pub enum What {
 }
 
 impl FromStr for What {
-    type Err = ParseIntError;
-
-    fn from_str(s: &str) -> Result<What, ParseIntError> {
-        Ok(match s {
-            "falls asleep" => What::FallsAsleep,
-            "wakes up" => What::WakesUp,
-            s => What::Begins(
-                s.split(' ')
-                    .skip(1)
-                    .next()
-                    .unwrap()
-                    .trim_start_matches('#')
-                    .parse()?,
-            ),
-        })
+    type Err = EventParseError;
+
+    fn from_str(s: &str) -> Result<What, EventParseError> {
+        match s {
+            "falls asleep" => Ok(What::FallsAsleep),
+            "wakes up" => Ok(What::WakesUp),
+            s if s.starts_with("Guard ") && s.ends_with(" begins shift") => s
+                .split(' ')
+                .nth(1)
+                .and_then(|id| id.strip_prefix('#'))
+                .and_then(|id| id.parse().ok())
+                .map(What::Begins)
+                .ok_or_else(|| EventParseError::MissingGuardId {
+                    line: None,
+                    text: s.to_string(),
+                }),
+            s => Err(EventParseError::UnknownAction {
+                line: None,
+                text: s.to_string(),
+            }),
+        }
     }
 }
 
@@ -169,6 +248,116 @@ impl fmt::Display for What {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shift {
+    pub guard: u16,
+    // Guards starting in the evening are on duty the next day
+    pub date: NaiveDate,
+    pub start: Time,
+    pub naps: Vec<Range<Time>>,
+}
+
+impl Shift {
+    pub fn minutes_asleep(&self) -> i64 {
+        self.naps.iter().map(|nap| nap.end - nap.start).sum()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Problem {
+    // Something happened before any guard began a shift
+    NoGuard { time: Time },
+    // Fell asleep while already asleep; the earlier nap start is kept
+    DoubleSleep { guard: u16, time: Time },
+    WakeWithoutSleep { guard: u16, time: Time },
+    // Still asleep when the next shift began or the log ran out; the nap is dropped
+    EndsAsleep { guard: u16, since: Time },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Problem::NoGuard { time } => write!(f, "{}: no guard on duty", time),
+            Problem::DoubleSleep { guard, time } => {
+                write!(f, "{}: guard #{} fell asleep twice", time, guard)
+            }
+            Problem::WakeWithoutSleep { guard, time } => {
+                write!(f, "{}: guard #{} woke up without sleeping", time, guard)
+            }
+            Problem::EndsAsleep { guard, since } => write!(
+                f,
+                "{}: guard #{} fell asleep and never woke up",
+                since, guard
+            ),
+        }
+    }
+}
+
+// Sorts the events and splits them into shifts, skipping over anything that doesn't make sense and
+// reporting it
+pub fn shifts(events: &[Event]) -> (Vec<Shift>, Vec<Problem>) {
+    let mut sorted = events.iter().collect::<Vec<_>>();
+    sorted.sort();
+
+    let mut shifts: Vec<Shift> = Vec::new();
+    let mut problems = Vec::new();
+    let mut asleep_since = None;
+    for event in sorted {
+        if let What::Begins(guard) = event.what {
+            if let (Some(since), Some(shift)) = (asleep_since.take(), shifts.last()) {
+                problems.push(Problem::EndsAsleep {
+                    guard: shift.guard,
+                    since,
+                });
+            }
+            let start = NaiveDateTime::from(event.time);
+            let date = if start.hour() < 12 {
+                start.date()
+            } else {
+                start.date() + Duration::days(1)
+            };
+            shifts.push(Shift {
+                guard,
+                date,
+                start: event.time,
+                naps: Vec::new(),
+            });
+            continue;
+        }
+
+        let shift = match shifts.last_mut() {
+            Some(shift) => shift,
+            None => {
+                problems.push(Problem::NoGuard { time: event.time });
+                continue;
+            }
+        };
+        match (event.what, asleep_since) {
+            (What::FallsAsleep, None) => asleep_since = Some(event.time),
+            (What::FallsAsleep, Some(_)) => problems.push(Problem::DoubleSleep {
+                guard: shift.guard,
+                time: event.time,
+            }),
+            (What::WakesUp, Some(since)) => {
+                shift.naps.push(since..event.time);
+                asleep_since = None;
+            }
+            (What::WakesUp, None) => problems.push(Problem::WakeWithoutSleep {
+                guard: shift.guard,
+                time: event.time,
+            }),
+            (What::Begins(_), _) => unreachable!(),
+        }
+    }
+    if let (Some(since), Some(shift)) = (asleep_since, shifts.last()) {
+        problems.push(Problem::EndsAsleep {
+            guard: shift.guard,
+            since,
+        });
+    }
+    (shifts, problems)
+}
+
 #[cfg(test)]
 #[test]
 fn test_event_parse() {
@@ -212,3 +401,156 @@ fn test_event_parse() {
         })
     );
 }
+
+#[cfg(test)]
+#[test]
+fn test_event_parse_error() {
+    let err = |s: &str| s.parse::<Event>().unwrap_err();
+    assert_eq!(
+        err("[1518-11-01 00:05] fals asleep"),
+        EventParseError::UnknownAction {
+            line: None,
+            text: "fals asleep".to_string()
+        }
+    );
+    assert_eq!(
+        err("[1518-11-01 00:00] Guard begins shift"),
+        EventParseError::MissingGuardId {
+            line: None,
+            text: "Guard begins shift".to_string()
+        }
+    );
+    assert_eq!(
+        err("[1518-11-01 00:00] Guard #x begins shift"),
+        EventParseError::MissingGuardId {
+            line: None,
+            text: "Guard #x begins shift".to_string()
+        }
+    );
+    for s in &[
+        "[1518-11-01 00:5x] wakes up",
+        "[1518-11-01] wakes up",
+        "[1518-13-01 00:05] wakes up",
+        "[1518-02-30 00:05] wakes up",
+        "[1518-11-01 00:60] wakes up",
+        "[4294967295-11-01 00:00] wakes up",
+        "[2147483648-11-01 00:00] wakes up",
+        "1518-11-01 00:05 wakes up",
+        "",
+    ] {
+        match err(s) {
+            EventParseError::BadTimestamp { line: None, .. } => {}
+            err => panic!("{:?}: {:?}", s, err),
+        }
+    }
+
+    let log = "[1518-11-01 00:00] Guard #10 begins shift\n\
+               [1518-11-01 00:05] falls asleep\n\
+               [1518-11-01 00:25] wakes up!\n";
+    let events = read_events_from(log.as_bytes()).collect::<Vec<_>>();
+    assert!(events[0].is_ok() && events[1].is_ok());
+    let err = events[2].as_ref().unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<EventParseError>(),
+        Some(&EventParseError::UnknownAction {
+            line: Some(3),
+            text: "wakes up!".to_string()
+        })
+    );
+    assert_eq!(err.to_string(), "line 3: unknown action \"wakes up!\"");
+    assert_eq!(
+        "[1518-11-01 00:00] wakes up!"
+            .parse::<Event>()
+            .unwrap_err()
+            .to_string(),
+        "unknown action \"wakes up!\""
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_shifts() {
+    let events = read_events_from(
+        "[1518-11-01 00:05] falls asleep
+[1518-11-01 00:00] Guard #10 begins shift
+[1518-11-01 00:25] wakes up
+[1518-11-01 00:30] falls asleep
+[1518-11-01 00:55] wakes up
+[1518-11-01 23:58] Guard #99 begins shift
+[1518-11-02 00:40] falls asleep
+[1518-11-02 00:50] wakes up
+[1518-11-03 00:05] Guard #10 begins shift
+[1518-11-03 00:24] falls asleep
+[1518-11-03 00:29] wakes up"
+            .as_bytes(),
+    )
+    .collect::<Fallible<Vec<_>>>()
+    .unwrap();
+    let (shifts, problems) = shifts(&events);
+    assert!(problems.is_empty());
+    assert_eq!(
+        shifts
+            .iter()
+            .map(|shift| (shift.guard, shift.date.day(), shift.minutes_asleep()))
+            .collect::<Vec<_>>(),
+        vec![(10, 1, 45), (99, 2, 10), (10, 3, 5)]
+    );
+    assert_eq!(shifts[0].naps[1].start.minute, 30);
+    assert_eq!(shifts[0].naps[1].end.minute, 55);
+}
+
+#[cfg(test)]
+#[test]
+fn test_shift_problems() {
+    let events = read_events_from(
+        "[1518-11-01 00:00] falls asleep
+[1518-11-01 00:01] Guard #10 begins shift
+[1518-11-01 00:05] falls asleep
+[1518-11-01 00:10] falls asleep
+[1518-11-01 00:25] wakes up
+[1518-11-01 00:30] wakes up
+[1518-11-01 00:40] falls asleep
+[1518-11-02 00:00] Guard #11 begins shift
+[1518-11-02 00:20] falls asleep"
+            .as_bytes(),
+    )
+    .collect::<Fallible<Vec<_>>>()
+    .unwrap();
+    let (shifts, problems) = shifts(&events);
+    let time = |day, minute| Time {
+        year: 1518,
+        month: 11,
+        day,
+        hour: 0,
+        minute,
+    };
+    assert_eq!(
+        problems,
+        vec![
+            Problem::NoGuard { time: time(1, 0) },
+            Problem::DoubleSleep {
+                guard: 10,
+                time: time(1, 10)
+            },
+            Problem::WakeWithoutSleep {
+                guard: 10,
+                time: time(1, 30)
+            },
+            Problem::EndsAsleep {
+                guard: 10,
+                since: time(1, 40)
+            },
+            Problem::EndsAsleep {
+                guard: 11,
+                since: time(2, 20)
+            },
+        ]
+    );
+    assert_eq!(shifts.len(), 2);
+    assert_eq!(shifts[0].naps, vec![time(1, 5)..time(1, 25)]);
+    assert!(shifts[1].naps.is_empty());
+    assert_eq!(
+        problems[2].to_string(),
+        "1518-11-1 0:30: guard #10 woke up without sleeping"
+    );
+}
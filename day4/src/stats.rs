@@ -0,0 +1,406 @@
+use crate::{Shift, Time};
+use failure::Fallible;
+use std::ops::Range;
+use std::str::FromStr;
+
+const MINUTES_PER_DAY: usize = 24 * 60;
+
+// A stretch of the day to count naps over, possibly running past midnight. Histograms have one
+// bucket per minute of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Window {
+    // Minutes past midnight
+    start: usize,
+    len: usize,
+}
+
+impl Window {
+    // What the puzzle looks at
+    pub const MIDNIGHT_HOUR: Window = Window { start: 0, len: 60 };
+    pub const FULL_DAY: Window = Window {
+        start: 0,
+        len: MINUTES_PER_DAY,
+    };
+
+    // Windows longer than a day are cut down to one
+    pub fn new(hour: u32, minute: u32, len: usize) -> Window {
+        Window {
+            start: (hour as usize * 60 + minute as usize) % MINUTES_PER_DAY,
+            len: len.min(MINUTES_PER_DAY),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Which bucket a time of day falls into, whatever the date
+    pub fn bucket(&self, time: Time) -> Option<usize> {
+        let minute = time.hour as usize * 60 + time.minute as usize;
+        let offset = (minute + MINUTES_PER_DAY - self.start) % MINUTES_PER_DAY;
+        if offset < self.len {
+            Some(offset)
+        } else {
+            None
+        }
+    }
+
+    // The hour and minute a bucket starts at
+    pub fn time_of_day(&self, bucket: usize) -> (u32, u32) {
+        let minute = (self.start + bucket) % MINUTES_PER_DAY;
+        ((minute / 60) as u32, (minute % 60) as u32)
+    }
+
+    // How many of the naps cover each minute of the window
+    pub fn histogram<'a>(&self, naps: impl IntoIterator<Item = &'a Range<Time>>) -> Vec<usize> {
+        let mut histogram = vec![0; self.len];
+        for nap in naps {
+            for time in nap.start.range(nap.end) {
+                if let Some(bucket) = self.bucket(time) {
+                    histogram[bucket] += 1;
+                }
+            }
+        }
+        histogram
+    }
+}
+
+// "day", "hour", or a start and length like "23:30+90"
+impl FromStr for Window {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Fallible<Window> {
+        match s {
+            "day" => return Ok(Window::FULL_DAY),
+            "hour" => return Ok(Window::MIDNIGHT_HOUR),
+            _ => {}
+        }
+        let bad =
+            || failure::format_err!("bad window {:?}, expected day, hour or HH:MM+MINUTES", s);
+        let (start, len) = s.split_once('+').ok_or_else(bad)?;
+        let (hour, minute) = start.split_once(':').ok_or_else(bad)?;
+        let (hour, minute, len) = (
+            hour.parse::<u32>().map_err(|_| bad())?,
+            minute.parse::<u32>().map_err(|_| bad())?,
+            len.parse::<usize>().map_err(|_| bad())?,
+        );
+        if hour >= 24 || minute >= 60 || len == 0 {
+            return Err(bad());
+        }
+        Ok(Window::new(hour, minute, len))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuardStats {
+    pub guard: u16,
+    pub shifts: usize,
+    // Shifts with at least one nap
+    pub nights_asleep: usize,
+    pub minutes_asleep: usize,
+    pub longest_nap: usize,
+    pub window: Window,
+    // How many naps covered each minute of the window
+    pub by_minute: Vec<usize>,
+}
+
+impl GuardStats {
+    // One entry per guard, ordered by guard ID, counting naps over the midnight hour
+    pub fn from_shifts(shifts: &[Shift]) -> Vec<GuardStats> {
+        GuardStats::from_shifts_in(shifts, Window::MIDNIGHT_HOUR)
+    }
+
+    pub fn from_shifts_in(shifts: &[Shift], window: Window) -> Vec<GuardStats> {
+        let mut stats: Vec<GuardStats> = Vec::new();
+        for shift in shifts {
+            let i = match stats.binary_search_by_key(&shift.guard, |stats| stats.guard) {
+                Ok(i) => i,
+                Err(i) => {
+                    stats.insert(
+                        i,
+                        GuardStats {
+                            guard: shift.guard,
+                            shifts: 0,
+                            nights_asleep: 0,
+                            minutes_asleep: 0,
+                            longest_nap: 0,
+                            window,
+                            by_minute: vec![0; window.len()],
+                        },
+                    );
+                    i
+                }
+            };
+            let entry = &mut stats[i];
+            entry.shifts += 1;
+            if !shift.naps.is_empty() {
+                entry.nights_asleep += 1;
+            }
+            for nap in &shift.naps {
+                let minutes = (nap.end - nap.start) as usize;
+                entry.minutes_asleep += minutes;
+                entry.longest_nap = entry.longest_nap.max(minutes);
+            }
+            let histogram = window.histogram(&shift.naps);
+            for (total, n) in entry.by_minute.iter_mut().zip(histogram) {
+                *total += n;
+            }
+        }
+        stats
+    }
+
+    // The minute of the window the guard was most often asleep, and how often; the latest minute
+    // wins ties
+    pub fn sleepiest_minute(&self) -> (usize, usize) {
+        let (times, minute) = self
+            .by_minute
+            .iter()
+            .enumerate()
+            .map(|(minute, times)| (*times, minute))
+            .max()
+            .unwrap_or((0, 0));
+        (minute, times)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Answer {
+    pub guard: u16,
+    pub window: Window,
+    // Minutes into the window, which over the midnight hour is the minute past midnight
+    pub minute: usize,
+    // How many naps covered that minute
+    pub times: usize,
+    // What the strategy ranked the guard by
+    pub score: usize,
+}
+
+impl Answer {
+    pub fn time_of_day(&self) -> (u32, u32) {
+        self.window.time_of_day(self.minute)
+    }
+
+    // What the puzzle asks for: the guard's ID times the minute. Over other windows that's minutes
+    // into the window, so each minute of it gives a different checksum; 01:24 in a window starting
+    // at 23:00 counts as 144, not 24.
+    pub fn checksum(&self) -> usize {
+        usize::from(self.guard) * self.minute
+    }
+}
+
+pub trait Strategy {
+    fn name(&self) -> &str;
+
+    // The guard with the highest score gets picked, the higher ID on ties
+    fn score(&self, stats: &GuardStats) -> usize;
+
+    // Only guards who slept during the window are considered, though they're still scored on the
+    // whole day
+    fn choose(&self, stats: &[GuardStats]) -> Option<Answer> {
+        let stats = stats
+            .iter()
+            .filter(|stats| stats.by_minute.iter().any(|n| *n > 0))
+            .max_by_key(|stats| (self.score(stats), stats.guard))?;
+        let (minute, times) = stats.sleepiest_minute();
+        Some(Answer {
+            guard: stats.guard,
+            window: stats.window,
+            minute,
+            times,
+            score: self.score(stats),
+        })
+    }
+}
+
+// Strategy 1
+pub struct MostAsleep;
+
+impl Strategy for MostAsleep {
+    fn name(&self) -> &str {
+        "most minutes asleep"
+    }
+
+    fn score(&self, stats: &GuardStats) -> usize {
+        stats.minutes_asleep
+    }
+}
+
+// Strategy 2
+pub struct MostFrequentMinute;
+
+impl Strategy for MostFrequentMinute {
+    fn name(&self) -> &str {
+        "most often asleep on the same minute"
+    }
+
+    fn score(&self, stats: &GuardStats) -> usize {
+        stats.sleepiest_minute().1
+    }
+}
+
+pub struct LongestNap;
+
+impl Strategy for LongestNap {
+    fn name(&self) -> &str {
+        "longest single nap"
+    }
+
+    fn score(&self, stats: &GuardStats) -> usize {
+        stats.longest_nap
+    }
+}
+
+pub struct MostNights;
+
+impl Strategy for MostNights {
+    fn name(&self) -> &str {
+        "most nights asleep"
+    }
+
+    fn score(&self, stats: &GuardStats) -> usize {
+        stats.nights_asleep
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_strategies() {
+    // The example from the puzzle
+    let events = crate::read_events_from(
+        "[1518-11-01 00:00] Guard #10 begins shift
+[1518-11-01 00:05] falls asleep
+[1518-11-01 00:25] wakes up
+[1518-11-01 00:30] falls asleep
+[1518-11-01 00:55] wakes up
+[1518-11-01 23:58] Guard #99 begins shift
+[1518-11-02 00:40] falls asleep
+[1518-11-02 00:50] wakes up
+[1518-11-03 00:05] Guard #10 begins shift
+[1518-11-03 00:24] falls asleep
+[1518-11-03 00:29] wakes up
+[1518-11-04 00:02] Guard #99 begins shift
+[1518-11-04 00:36] falls asleep
+[1518-11-04 00:46] wakes up
+[1518-11-05 00:03] Guard #99 begins shift
+[1518-11-05 00:45] falls asleep
+[1518-11-05 00:55] wakes up"
+            .as_bytes(),
+    )
+    .collect::<failure::Fallible<Vec<_>>>()
+    .unwrap();
+    let stats = GuardStats::from_shifts(&crate::shifts(&events).0);
+    assert_eq!(
+        stats
+            .iter()
+            .map(|stats| (stats.guard, stats.shifts, stats.minutes_asleep))
+            .collect::<Vec<_>>(),
+        vec![(10, 2, 50), (99, 3, 30)]
+    );
+
+    assert_eq!(
+        MostAsleep.choose(&stats),
+        Some(Answer {
+            guard: 10,
+            window: Window::MIDNIGHT_HOUR,
+            minute: 24,
+            times: 2,
+            score: 50
+        })
+    );
+    assert_eq!(MostAsleep.choose(&stats).unwrap().checksum(), 240);
+    assert_eq!(
+        MostFrequentMinute.choose(&stats),
+        Some(Answer {
+            guard: 99,
+            window: Window::MIDNIGHT_HOUR,
+            minute: 45,
+            times: 3,
+            score: 3
+        })
+    );
+    assert_eq!(MostFrequentMinute.choose(&stats).unwrap().checksum(), 4455);
+    assert_eq!(LongestNap.choose(&stats).unwrap().guard, 10);
+    assert_eq!(LongestNap.choose(&stats).unwrap().score, 25);
+    assert_eq!(MostNights.choose(&stats).unwrap().guard, 99);
+    assert_eq!(MostAsleep.choose(&[]), None);
+
+    // Guards who stayed awake are never the answer
+    let awake = crate::read_events_from(
+        "[1518-11-01 00:00] Guard #10 begins shift\n[1518-11-02 00:00] Guard #11 begins shift"
+            .as_bytes(),
+    )
+    .collect::<failure::Fallible<Vec<_>>>()
+    .unwrap();
+    let stats = GuardStats::from_shifts(&crate::shifts(&awake).0);
+    assert_eq!(stats.len(), 2);
+    assert_eq!(MostAsleep.choose(&stats), None);
+    assert_eq!(MostNights.choose(&stats), None);
+}
+
+#[cfg(test)]
+#[test]
+fn test_windows() {
+    let events = crate::read_events_from(
+        "[1518-11-01 23:50] Guard #10 begins shift
+[1518-11-01 23:58] falls asleep
+[1518-11-02 00:02] wakes up
+[1518-11-02 00:58] falls asleep
+[1518-11-02 01:01] wakes up
+[1518-11-02 23:59] Guard #11 begins shift
+[1518-11-03 00:30] falls asleep
+[1518-11-03 00:31] wakes up"
+            .as_bytes(),
+    )
+    .collect::<failure::Fallible<Vec<_>>>()
+    .unwrap();
+    let (shifts, _) = crate::shifts(&events);
+
+    // Over the midnight hour, 23:58 and 01:00 don't count
+    let stats = GuardStats::from_shifts(&shifts);
+    assert_eq!(stats[0].minutes_asleep, 7);
+    assert_eq!(stats[0].by_minute.iter().sum::<usize>(), 4);
+    assert_eq!(&stats[0].by_minute[..3], &[1, 1, 0]);
+    assert_eq!(&stats[0].by_minute[58..], &[1, 1]);
+
+    let stats = GuardStats::from_shifts_in(&shifts, Window::FULL_DAY);
+    assert_eq!(stats[0].by_minute.len(), 1440);
+    assert_eq!(stats[0].by_minute.iter().sum::<usize>(), 7);
+    assert_eq!(&stats[0].by_minute[1438..], &[1, 1]);
+    assert_eq!(stats[0].by_minute[60], 1);
+
+    let window = "23:55+10".parse::<Window>().unwrap();
+    let stats = GuardStats::from_shifts_in(&shifts, window);
+    assert_eq!(stats[0].by_minute, vec![0, 0, 0, 1, 1, 1, 1, 0, 0, 0]);
+    assert_eq!(stats[0].sleepiest_minute(), (6, 1));
+    assert_eq!(window.time_of_day(6), (0, 1));
+    assert!(stats[1].by_minute.iter().all(|n| *n == 0));
+    assert_eq!(
+        MostAsleep.choose(&GuardStats::from_shifts_in(&shifts, Window::new(12, 0, 60))),
+        None
+    );
+    let answer = MostAsleep.choose(&stats).unwrap();
+    assert_eq!((answer.guard, answer.time_of_day()), (10, (0, 1)));
+    assert_eq!(answer.checksum(), 60);
+
+    // Guard 10 slept the most, but not at 00:30
+    let answer = MostAsleep
+        .choose(&GuardStats::from_shifts_in(
+            &shifts,
+            "00:30+1".parse().unwrap(),
+        ))
+        .unwrap();
+    assert_eq!(
+        (answer.guard, answer.time_of_day(), answer.score),
+        (11, (0, 30), 1)
+    );
+
+    assert_eq!(window.bucket(shifts[0].start), None);
+    assert_eq!(Window::new(23, 0, 5000), Window::new(23, 0, 1440));
+    assert_eq!("hour".parse::<Window>().unwrap(), Window::MIDNIGHT_HOUR);
+    assert!("24:00+5".parse::<Window>().is_err());
+    assert!("00:00+0".parse::<Window>().is_err());
+}